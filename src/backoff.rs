@@ -0,0 +1,95 @@
+use std::cmp;
+use std::time::Duration;
+use rand::Rng;
+
+/// Exponential backoff to wait between retries.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+	base: Duration,
+	max: Duration,
+	attempt: u32,
+}
+
+impl Backoff {
+	pub fn new(base: Duration, max: Duration) -> Self {
+		Backoff {
+			base,
+			max,
+			attempt: 0,
+		}
+	}
+
+	/// Get the duration to wait before the next retry and count up the attempt.
+	pub fn next_delay(&mut self) -> Duration {
+		let delay = self.delay(self.attempt);
+		self.attempt = self.attempt.saturating_add(1);
+		delay
+	}
+
+	/// Get the randomized duration to wait before the next retry and count up the attempt.
+	///
+	/// The duration is between the half and the whole of `next_delay`
+	/// so that many clients don't retry at the same time.
+	pub fn next_delay_with_jitter(&mut self) -> Duration {
+		let delay = self.next_delay();
+		let half = delay / 2;
+		half + half.mul_f64(rand::thread_rng().gen_range(0.0, 1.0))
+	}
+
+	/// Get the duration to wait before the retry of the specified attempt.
+	pub fn delay(&self, attempt: u32) -> Duration {
+		let factor = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+		match self.base.checked_mul(factor) {
+			Some(delay) => cmp::min(delay, self.max),
+			None => self.max,
+		}
+	}
+
+	pub fn reset(&mut self) {
+		self.attempt = 0;
+	}
+
+	pub fn attempt(&self) -> u32 {
+		self.attempt
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_delay() {
+		let backoff = Backoff::new(Duration::from_secs(30), Duration::from_secs(3600));
+
+		assert_eq!(backoff.delay(0), Duration::from_secs(30));
+		assert_eq!(backoff.delay(1), Duration::from_secs(60));
+		assert_eq!(backoff.delay(3), Duration::from_secs(240));
+		assert_eq!(backoff.delay(7), Duration::from_secs(3600));
+		assert_eq!(backoff.delay(100), Duration::from_secs(3600));
+	}
+
+	#[test]
+	fn test_next_delay_and_reset() {
+		let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10));
+
+		assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+		assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+		assert_eq!(backoff.next_delay(), Duration::from_secs(4));
+		assert_eq!(backoff.attempt(), 3);
+
+		backoff.reset();
+		assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+	}
+
+	#[test]
+	fn test_next_delay_with_jitter() {
+		let mut backoff = Backoff::new(Duration::from_secs(10), Duration::from_secs(40));
+
+		for max in [10, 20, 40, 40].iter() {
+			let delay = backoff.next_delay_with_jitter();
+			assert!(delay >= Duration::from_secs(max / 2));
+			assert!(delay <= Duration::from_secs(*max));
+		}
+	}
+}
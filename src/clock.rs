@@ -0,0 +1,69 @@
+use std::sync::{ Arc, Mutex };
+use chrono::{ DateTime, Duration };
+use chrono_tz::Tz;
+
+/// Source of the current time, replaceable to control time-dependent behavior.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+	fn now(&self) -> DateTime<Tz>;
+}
+
+/// Clock which returns the time of the system in the timezone of the game.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+	pub fn shared() -> Arc<dyn Clock> {
+		Arc::new(SystemClock)
+	}
+}
+
+impl Clock for SystemClock {
+	fn now(&self) -> DateTime<Tz> {
+		crate::timezone::now()
+	}
+}
+
+/// Clock which stays at the time set manually, clones share the same time.
+#[derive(Debug, Clone)]
+pub struct FakeClock {
+	now: Arc<Mutex<DateTime<Tz>>>,
+}
+
+impl FakeClock {
+	pub fn new(now: DateTime<Tz>) -> Self {
+		FakeClock {
+			now: Arc::new(Mutex::new(now)),
+		}
+	}
+
+	#[cfg(test)]
+	pub fn advance(&self, duration: Duration) {
+		let mut now = self.now.lock().unwrap();
+		*now = *now + duration;
+	}
+}
+
+impl Clock for FakeClock {
+	fn now(&self) -> DateTime<Tz> {
+		*self.now.lock().unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+	use chrono_tz::Asia::Tokyo;
+
+	#[test]
+	fn test_fake_clock() {
+		let at = Tokyo.with_ymd_and_hms(2020, 7, 25, 6, 1, 30).unwrap();
+		let clock = FakeClock::new(at);
+		let shared = clock.clone();
+
+		assert_eq!(shared.now(), at);
+
+		clock.advance(Duration::minutes(1));
+		assert_eq!(shared.now(), Tokyo.with_ymd_and_hms(2020, 7, 25, 6, 2, 30).unwrap());
+	}
+}
@@ -0,0 +1,106 @@
+use std::sync::{ Arc, mpsc };
+use chrono::DateTime;
+use mastors::entities::{ Notification, Status };
+use crate::{
+	Message,
+	Monsters,
+	clock::FakeClock,
+	fake,
+	features::response::{
+		NotificationProcessor,
+		StatusProcessor,
+	},
+	timezone,
+};
+
+const DEFAULT_ACCT: &str = "drakeema-tester";
+const DEFAULT_VISIBILITY: &str = "public";
+
+/// Pass the text to the processors as a mention to the bot, and print the messages they send
+/// without connecting to the server.
+pub fn run(args: &clap::ArgMatches) -> i32 {
+	// Safe unwrapping because the text is a required argument.
+	let text = args.value_of("text").unwrap();
+	let acct = args.value_of("acct").unwrap_or(DEFAULT_ACCT);
+	let visibility = args.value_of("visibility").unwrap_or(DEFAULT_VISIBILITY);
+
+	let at = match args.value_of("at") {
+		Some(at) => match DateTime::parse_from_rfc3339(at) {
+			Ok(at) => at.with_timezone(&timezone::timezone()),
+			Err(e) => {
+				eprintln!("--at must be given in RFC 3339 format such as 2020-09-06T06:00:00+09:00: {}", e);
+				return 1;
+			},
+		},
+		None => timezone::now(),
+	};
+
+	let status: Status = match serde_json::from_value(fake::status(text, acct, visibility, at)) {
+		Ok(status) => status,
+		Err(e) => {
+			eprintln!("Failed to build a status: {}", e);
+			return 1;
+		},
+	};
+	let notification: Notification = match serde_json::from_value(fake::notification(text, acct, visibility, at)) {
+		Ok(notification) => notification,
+		Err(e) => {
+			eprintln!("Failed to build a notification: {}", e);
+			return 1;
+		},
+	};
+
+	let clock = Arc::new(FakeClock::new(at));
+	let status_processor = Monsters::load().and_then(|monsters| StatusProcessor::load(clock, &monsters));
+	let (status_processor, notification_processor) = match (status_processor, NotificationProcessor::load()) {
+		(Ok(sp), Ok(np)) => (sp, np),
+		(Err(e), _) | (_, Err(e)) => {
+			eprintln!("Failed to load processors: {}", e);
+			return 1;
+		},
+	};
+
+	let (tx, rx) = mpsc::channel();
+	match status_processor.process(&tx, &status) {
+		Some(branch) => println!("branch: {}", branch),
+		None => println!("branch: (no response)"),
+	};
+	notification_processor.process(&tx, &notification);
+	drop(tx);
+
+	for message in rx {
+		print_message(&message);
+	}
+
+	0
+}
+
+fn print_message(message: &Message) {
+	match message {
+		Message::Status { text, spoiler_text, language, visibility, mention, in_reply_to_id, poll_options, priority, .. } => {
+			println!("--- status");
+			println!("visibility: {:?}, priority: {:?}", visibility, priority);
+			if let Some(mention) = mention {
+				println!("mention: @{}", mention);
+			}
+			if let Some(in_reply_to_id) = in_reply_to_id {
+				println!("in_reply_to_id: {}", in_reply_to_id);
+			}
+			if let Some(spoiler_text) = spoiler_text {
+				println!("spoiler_text: {}", spoiler_text);
+			}
+			if let Some(language) = language {
+				println!("language: {}", language);
+			}
+			if let Some(poll_options) = poll_options {
+				println!("poll: {:?}, expires_in: {}", poll_options.poll_options(), poll_options.expires_in());
+			}
+			println!("{}", text);
+		},
+		Message::Follow(account) => println!("--- follow: {}", account.acct()),
+		Message::Unfollow(account) => println!("--- unfollow: {}", account.acct()),
+		Message::Reload(_) => println!("--- reload"),
+		Message::Shutdown => println!("--- shutdown"),
+		Message::Error(text, e) => println!("--- error: {}: {}", text, e),
+	};
+}
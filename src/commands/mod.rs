@@ -0,0 +1,3 @@
+pub mod ask;
+pub mod simulate;
+pub mod validate;
@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+use crate::{
+	Monsters,
+	features::announcement::Contents,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Print the announcements about contents at every announcement time between the dates.
+/// Announcements which need the connection to the server, such as weekly activities, are skipped.
+pub fn run(args: &clap::ArgMatches) -> i32 {
+	let (from, to) = match (parse_date(args.value_of("from")), parse_date(args.value_of("to"))) {
+		(Some(from), Some(to)) if from <= to => (from, to),
+		(Some(_), Some(_)) => {
+			eprintln!("--from must not be after --to");
+			return 1;
+		},
+		_ => {
+			eprintln!("Dates must be given in the format of YYYY-MM-DD");
+			return 1;
+		},
+	};
+
+	let contents = match Monsters::load().and_then(|monsters| Contents::load_offline(&monsters)) {
+		Ok(contents) => contents,
+		Err(e) => {
+			eprintln!("Failed to load contents: {}", e);
+			return 1;
+		},
+	};
+
+	for (at, announcements) in contents.simulate(from, to) {
+		println!("=== {}", at.format("%Y-%m-%d %H:%M:%S"));
+		if announcements.is_empty() {
+			println!("(no announcement)");
+		}
+		for announcement in announcements {
+			let text = announcement.render();
+			println!("--- visibility: {:?}, priority: {:?}", announcement.visibility(), announcement.priority());
+			if let Some(spoiler_text) = text.spoiler_text() {
+				println!("CW: {}", spoiler_text);
+			}
+			println!("{}", text.text());
+		}
+		println!();
+	}
+
+	0
+}
+
+fn parse_date(s: Option<&str>) -> Option<NaiveDate> {
+	NaiveDate::parse_from_str(s?, DATE_FORMAT).ok()
+}
@@ -0,0 +1,20 @@
+use crate::validation;
+
+/// Load all data files and print the problems found in them.
+/// Return the exit code which is not zero when any problem is found.
+pub fn run() -> i32 {
+	info!("Start validating data files");
+	let validator = validation::validate_data_files();
+
+	for problem in validator.problems() {
+		println!("{}", problem);
+	}
+
+	if validator.problems().is_empty() {
+		println!("No problems found");
+		0
+	} else {
+		println!("{} problems found", validator.problems().len());
+		1
+	}
+}
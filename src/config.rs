@@ -0,0 +1,300 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{ Path, PathBuf };
+use std::sync::OnceLock;
+use serde::Deserialize;
+use crate::{
+	Error,
+	Result,
+};
+
+const DEFAULT_CONFIG_FILE: &str = "drakeema.json";
+const DEFAULT_DATA_DIR: &str = "drakeema-data";
+const DEFAULT_STATE_DIR_NAME: &str = "tmp";
+const DEFAULT_STATUS_RATE_LIMIT: usize = 20;
+const DEFAULT_FOLLOW_RATE_LIMIT: usize = 10;
+const DEFAULT_MAX_CHARACTERS: usize = 500;
+
+const ENV_DATA_DIR: &str = "DRAKEEMA_DATA_DIR";
+const ENV_STATE_DIR: &str = "DRAKEEMA_STATE_DIR";
+const ENV_TIMEZONE: &str = "DRAKEEMA_TIMEZONE";
+const ENV_STATUS_RATE_LIMIT: &str = "DRAKEEMA_STATUS_RATE_LIMIT";
+const ENV_FOLLOW_RATE_LIMIT: &str = "DRAKEEMA_FOLLOW_RATE_LIMIT";
+const ENV_MAX_CHARACTERS: &str = "DRAKEEMA_MAX_CHARACTERS";
+const ENV_MAX_RETRY: &str = "DRAKEEMA_MAX_RETRY";
+const ENV_WORKERS: &str = "DRAKEEMA_WORKERS";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Set the runtime configuration, which can be set only once before it is used.
+pub fn init(config: Config) -> Result<()> {
+	CONFIG.set(config)
+		.map_err(|_| Error::InvalidConfig("-".to_owned(), "configuration is already initialized".to_owned()))
+}
+
+/// Get the runtime configuration, the default one is used when it has not been initialized.
+pub fn get() -> &'static Config {
+	CONFIG.get_or_init(Config::default)
+}
+
+/// Resolve the path of the data file against the data root.
+pub fn data_path(file: impl AsRef<Path>) -> PathBuf {
+	get().data_dir().join(file)
+}
+
+/// Resolve the path of the temporary file against the state directory.
+pub fn state_path(file: impl AsRef<Path>) -> PathBuf {
+	get().state_dir().join(file)
+}
+
+/// Runtime configuration, loaded from the configuration file and overridden
+/// by the environment variables and the command line options in this order.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	data_dir: PathBuf,
+	state_dir: Option<PathBuf>,
+	timezone: Option<String>,
+	status_rate_limit: usize,
+	follow_rate_limit: usize,
+	max_characters: usize,
+	max_retry: Option<usize>,
+	workers: Workers,
+}
+
+impl Config {
+	/// Load the configuration file.
+	/// The default file is optional, but the file given explicitly must exist.
+	pub fn load(file: Option<&str>) -> Result<Self> {
+		let (path, required) = match file {
+			Some(file) => (file, true),
+			None => (DEFAULT_CONFIG_FILE, false),
+		};
+
+		let mut config = if required || Path::new(path).exists() {
+			info!("Load the configuration file: {}", path);
+
+			serde_json::from_reader(
+				BufReader::new(File::open(path)?)
+			)
+			.map_err(|e| Error::UnparseableJson(path.to_owned(), e))?
+		} else {
+			info!("Configuration file is not found, use the default configuration: {}", path);
+			Config::default()
+		};
+
+		config.override_with(|key| std::env::var(key).ok())?;
+		Ok(config)
+	}
+
+	/// Override the configuration with the variables such as the environment variables.
+	fn override_with(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+		if let Some(dir) = var(ENV_DATA_DIR) {
+			self.data_dir = PathBuf::from(dir);
+		}
+		if let Some(dir) = var(ENV_STATE_DIR) {
+			self.state_dir = Some(PathBuf::from(dir));
+		}
+		if let Some(timezone) = var(ENV_TIMEZONE) {
+			self.timezone = Some(timezone);
+		}
+		if let Some(limit) = var(ENV_STATUS_RATE_LIMIT) {
+			self.status_rate_limit = parse_number(ENV_STATUS_RATE_LIMIT, &limit)?;
+		}
+		if let Some(limit) = var(ENV_FOLLOW_RATE_LIMIT) {
+			self.follow_rate_limit = parse_number(ENV_FOLLOW_RATE_LIMIT, &limit)?;
+		}
+		if let Some(max) = var(ENV_MAX_CHARACTERS) {
+			self.max_characters = parse_number(ENV_MAX_CHARACTERS, &max)?;
+		}
+		if let Some(max_retry) = var(ENV_MAX_RETRY) {
+			self.max_retry = if max_retry.is_empty() {
+				None
+			} else {
+				Some(parse_number(ENV_MAX_RETRY, &max_retry)?)
+			};
+		}
+		if let Some(workers) = var(ENV_WORKERS) {
+			self.workers = Workers::parse(&workers)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn set_data_dir(&mut self, dir: impl Into<PathBuf>) {
+		self.data_dir = dir.into();
+	}
+
+	pub fn data_dir(&self) -> &Path {
+		&self.data_dir
+	}
+
+	/// Directory for the temporary files, `tmp` in the data root by default.
+	pub fn state_dir(&self) -> PathBuf {
+		match self.state_dir.as_ref() {
+			Some(dir) => dir.to_owned(),
+			None => self.data_dir.join(DEFAULT_STATE_DIR_NAME),
+		}
+	}
+
+	pub fn timezone(&self) -> Option<&str> {
+		self.timezone.as_deref()
+	}
+
+	pub fn status_rate_limit(&self) -> usize {
+		self.status_rate_limit
+	}
+
+	pub fn follow_rate_limit(&self) -> usize {
+		self.follow_rate_limit
+	}
+
+	/// Maximum number of characters of a status, used when the instance doesn't tell it.
+	pub fn max_characters(&self) -> usize {
+		self.max_characters
+	}
+
+	/// Maximum number of retries to connect to the streaming API, `None` retries forever.
+	pub fn max_retry(&self) -> Option<usize> {
+		self.max_retry
+	}
+
+	pub fn workers(&self) -> &Workers {
+		&self.workers
+	}
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Config {
+			data_dir: PathBuf::from(DEFAULT_DATA_DIR),
+			state_dir: None,
+			timezone: None,
+			status_rate_limit: DEFAULT_STATUS_RATE_LIMIT,
+			follow_rate_limit: DEFAULT_FOLLOW_RATE_LIMIT,
+			max_characters: DEFAULT_MAX_CHARACTERS,
+			max_retry: None,
+			workers: Workers::default(),
+		}
+	}
+}
+
+/// Workers to be run, all of them are enabled by default.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Workers {
+	contents: bool,
+	feeds: bool,
+	response: bool,
+}
+
+impl Workers {
+	/// Parse the comma separated names of the workers to be enabled.
+	fn parse(names: &str) -> Result<Self> {
+		let mut workers = Workers {
+			contents: false,
+			feeds: false,
+			response: false,
+		};
+
+		for name in names.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+			match name {
+				"contents" => workers.contents = true,
+				"feeds" => workers.feeds = true,
+				"response" => workers.response = true,
+				_ => return Err(Error::InvalidConfig(
+					ENV_WORKERS.to_owned(),
+					format!("unknown worker: {}", name),
+				)),
+			};
+		}
+
+		Ok(workers)
+	}
+
+	pub fn contents(&self) -> bool {
+		self.contents
+	}
+
+	pub fn feeds(&self) -> bool {
+		self.feeds
+	}
+
+	pub fn response(&self) -> bool {
+		self.response
+	}
+}
+
+impl Default for Workers {
+	fn default() -> Self {
+		Workers {
+			contents: true,
+			feeds: true,
+			response: true,
+		}
+	}
+}
+
+fn parse_number(key: &str, value: &str) -> Result<usize> {
+	value.trim().parse::<usize>()
+		.map_err(|e| Error::InvalidConfig(key.to_owned(), format!("{}: {}", value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	#[test]
+	fn test_default_paths() {
+		let config: Config = serde_json::from_str(r#"{ "data_dir": "/srv/drakeema" }"#).unwrap();
+
+		assert_eq!(config.data_dir(), Path::new("/srv/drakeema"));
+		assert_eq!(config.state_dir(), PathBuf::from("/srv/drakeema/tmp"));
+		assert_eq!(config.status_rate_limit(), 20);
+		assert_eq!(config.max_characters(), 500);
+		assert_eq!(config.workers(), &Workers::default());
+	}
+
+	#[test]
+	fn test_override_with() {
+		let mut config: Config = serde_json::from_str(r#"{
+			"state_dir": "/var/lib/drakeema",
+			"max_retry": 5,
+			"workers": { "feeds": false }
+		}"#).unwrap();
+
+		let vars: HashMap<&str, &str> = vec![
+			("DRAKEEMA_DATA_DIR", "/srv/drakeema"),
+			("DRAKEEMA_STATUS_RATE_LIMIT", "5"),
+			("DRAKEEMA_MAX_CHARACTERS", "3000"),
+			("DRAKEEMA_MAX_RETRY", ""),
+			("DRAKEEMA_WORKERS", "contents, response"),
+		].into_iter().collect();
+		config.override_with(|key| vars.get(key).map(|v| v.to_string())).unwrap();
+
+		assert_eq!(config.data_dir(), Path::new("/srv/drakeema"));
+		assert_eq!(config.state_dir(), PathBuf::from("/var/lib/drakeema"));
+		assert_eq!(config.status_rate_limit(), 5);
+		assert_eq!(config.follow_rate_limit(), 10);
+		assert_eq!(config.max_characters(), 3000);
+		assert_eq!(config.max_retry(), None);
+		assert!(config.workers().contents());
+		assert!(!config.workers().feeds());
+		assert!(config.workers().response());
+	}
+
+	#[test]
+	fn test_override_with_invalid_value() {
+		let mut config = Config::default();
+
+		assert!(config.override_with(|key| match key {
+			"DRAKEEMA_FOLLOW_RATE_LIMIT" => Some("ten".to_owned()),
+			_ => None,
+		}).is_err());
+		assert!(config.override_with(|key| match key {
+			"DRAKEEMA_WORKERS" => Some("contents,healthcheck".to_owned()),
+			_ => None,
+		}).is_err());
+	}
+}
@@ -1,34 +1,64 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ DateTime, Local };
+use std::sync::Arc;
+use chrono::DateTime;
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
-	monsters::Monster,
-	utils::transform_string_to_regex,
+	config,
+	monsters::{ Monster, Monsters },
+	utils::{
+		transform_string_to_datetime,
+		transform_string_to_regex,
+	},
+	validation::Validator,
 };
-use super::{ Responder, ResponseCriteria };
+use super::{ Responder, Response, ResponseCriteria, Template };
 
-const DATA: &str = "drakeema-data/contents/boueigun.json";
+const DATA: &str = "contents/boueigun.json";
 
 #[derive(Debug, Clone)]
-pub struct Boueigun<'a> {
-	monsters: BoueigunMonsters<'a>,
+pub struct Boueigun {
+	monsters: BoueigunMonsters,
 	total_duration: i64,
 	inner: BoueigunJson,
 }
 
-impl<'a> Boueigun<'a> {
-	pub fn load() -> Result<Self> {
+pub fn validate(validator: &mut Validator, monsters: &HashMap<String, Monster>) {
+	let json: BoueigunJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	validator.check_template(DATA, "information", &json.information, &[
+		"__LOCATION__", "__CURRENT_MONSTER__", "__RESISTANCES__", "__NEXT_MONSTER__", "__REMAIN__",
+	]);
+	validator.check_not_empty(DATA, "monsters", &json.monsters);
+
+	for (i, monster) in json.monsters.iter().enumerate() {
+		let field = format!("monsters[{}].monster_id", i);
+		if let Some(m) = validator.check_monster_id(DATA, &field, monsters, &monster.monster_id) {
+			validator.check_single_area(DATA, &field, m);
+		}
+		if monster.duration <= 0 {
+			validator.push(DATA, format!("monsters[{}].duration", i), "duration must be positive");
+		}
+	}
+}
+
+impl Boueigun {
+	pub fn load(monsters: &Monsters) -> Result<Self> {
 		info!("Initialize Boueigun");
 
 		let inner: BoueigunJson = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
-		let monsters = BoueigunMonsters::new(&inner.monsters)?;
+		let monsters = BoueigunMonsters::new(&inner.monsters, monsters)?;
 		let total_duration = monsters.iter()
 			.fold(0, |acc, m| acc + m.duration);
 		Ok(Boueigun {
@@ -38,14 +68,14 @@ impl<'a> Boueigun<'a> {
 		})
 	}
 
-	fn current_status(&self, at: DateTime<Local>) -> CurrentMonsterInfo {
+	fn current_status(&self, at: DateTime<Tz>) -> CurrentMonsterInfo {
 		let mut ref_date = self.reference_date;
 
 		let monsters = if at < self.reference_date {
 			ref_date = ref_date - chrono::Duration::nanoseconds(1);
-			self.monsters.iter().rev().collect::<Vec<&BoueigunMonster<'a>>>()
+			self.monsters.iter().rev().collect::<Vec<&BoueigunMonster>>()
 		} else {
-			self.monsters.iter().collect::<Vec<&BoueigunMonster<'a>>>()
+			self.monsters.iter().collect::<Vec<&BoueigunMonster>>()
 		};
 
 		let elapsed_min = (at - ref_date).num_minutes().abs();
@@ -73,22 +103,23 @@ impl<'a> Boueigun<'a> {
 	}
 }
 
-impl<'a> Responder for Boueigun<'a> {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+impl Responder for Boueigun {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		debug!("Start building response about Boueigun: {:?}", criteria);
 
 		if self.nickname_regex.is_match(criteria.text()) {
 			info!("Text matched keywords of Boueigun: {}", criteria.text());
 
 			let info = self.current_status(criteria.at());
-			let response = self.information
-				.replace("__LOCATION__", info.current.location.as_str())
-				.replace("__CURRENT_MONSTER__", info.current.display())
-				.replace("__RESISTANCES__", info.current.resistances().display(None::<Vec<String>>).as_str())
-				.replace("__NEXT_MONSTER__", info.next.display())
-				.replace("__REMAIN__", info.remain.to_string().as_str());
-
-			Some(response)
+			let response = self.information.render(&[
+				("__LOCATION__", info.current.location.as_str()),
+				("__CURRENT_MONSTER__", info.current.display()),
+				("__RESISTANCES__", info.current.resistances().display(None::<Vec<String>>).as_str()),
+				("__NEXT_MONSTER__", info.next.display()),
+				("__REMAIN__", info.remain.to_string().as_str()),
+			]);
+
+			Some(Response::from(response))
 		} else {
 			debug!("Nothing response about boueigun: {:?}", criteria);
 			None
@@ -96,7 +127,7 @@ impl<'a> Responder for Boueigun<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for Boueigun<'a> {
+impl std::ops::Deref for Boueigun {
 	type Target = BoueigunJson;
 
 	fn deref(&self) -> &Self::Target {
@@ -106,34 +137,33 @@ impl<'a> std::ops::Deref for Boueigun<'a> {
 
 #[derive(Debug, Clone)]
 struct CurrentMonsterInfo<'a> {
-	current: &'a BoueigunMonster<'a>,
-	next: &'a BoueigunMonster<'a>,
+	current: &'a BoueigunMonster,
+	next: &'a BoueigunMonster,
 	remain: i64,
 }
 
 #[derive(Debug, Clone)]
-struct BoueigunMonsters<'a> {
-	inner: Vec<BoueigunMonster<'a>>,
+struct BoueigunMonsters {
+	inner: Vec<BoueigunMonster>,
 }
 
-impl<'a> std::ops::Deref for BoueigunMonsters<'a> {
-	type Target = Vec<BoueigunMonster<'a>>;
+impl std::ops::Deref for BoueigunMonsters {
+	type Target = Vec<BoueigunMonster>;
 
 	fn deref(&self) -> &Self::Target {
 		&self.inner
 	}
 }
 
-impl<'a> BoueigunMonsters<'a> {
-	fn new(b_monsters: impl AsRef<[MonsterJson]>) -> Result<Self> {
-		let mut inner: Vec<BoueigunMonster<'a>> = Vec::new();
-		let monsters = crate::monsters();
+impl BoueigunMonsters {
+	fn new(b_monsters: impl AsRef<[MonsterJson]>, monsters: &Monsters) -> Result<Self> {
+		let mut inner: Vec<BoueigunMonster> = Vec::new();
 		for monster in b_monsters.as_ref() {
 			match monsters.get(&monster.monster_id) {
 				Some(m) => {
 					inner.push(BoueigunMonster {
 						id: monster.id.to_owned(),
-						monster: m,
+						monster: Arc::clone(m),
 						location: monster.location.to_owned(),
 						duration: monster.duration,
 					});
@@ -150,25 +180,26 @@ impl<'a> BoueigunMonsters<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct BoueigunMonster<'a> {
+struct BoueigunMonster {
 	#[allow(unused)]
 	id: String,
-	monster: &'a Monster,
+	monster: Arc<Monster>,
 	location: String,
 	duration: i64,
 }
 
-impl<'a> std::ops::Deref for BoueigunMonster<'a> {
+impl std::ops::Deref for BoueigunMonster {
 	type Target = Monster;
 	fn deref(&self) -> &Self::Target {
-		self.monster
+		&self.monster
 	}
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct BoueigunJson {
-	reference_date: DateTime<Local>,
-	information: String,
+	#[serde(deserialize_with = "transform_string_to_datetime")]
+	reference_date: DateTime<Tz>,
+	information: Template,
 	#[serde(deserialize_with = "transform_string_to_regex")]
 	nickname_regex: regex::Regex,
 	monsters: Vec<MonsterJson>,
@@ -185,27 +216,25 @@ struct MonsterJson {
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use chrono::{
-		Local,
-		TimeZone,
-	};
+	use chrono::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_current_positive() {
 		let bou = data();
 
 		assert_eq!(
-			bou.current_status(Local.with_ymd_and_hms(2021, 11, 15, 6, 0, 0).unwrap()).current.id,
+			bou.current_status(Tokyo.with_ymd_and_hms(2021, 11, 15, 6, 0, 0).unwrap()).current.id,
 			"ryurin"
 		);
 
 		assert_eq!(
-			bou.current_status(Local.with_ymd_and_hms(2021, 11, 15, 6, 59, 59).unwrap()).current.id,
+			bou.current_status(Tokyo.with_ymd_and_hms(2021, 11, 15, 6, 59, 59).unwrap()).current.id,
 			"ryurin"
 		);
 
 		assert_eq!(
-			bou.current_status(Local.with_ymd_and_hms(2021, 11, 15, 7, 00, 00).unwrap()).current.id,
+			bou.current_status(Tokyo.with_ymd_and_hms(2021, 11, 15, 7, 00, 00).unwrap()).current.id,
 			"nenkai"
 		);
 
@@ -226,17 +255,17 @@ pub(crate) mod tests {
 		let bou = data();
 
 		assert_eq!(
-			bou.current_status(Local.with_ymd_and_hms(2021, 11, 15, 5, 59, 59).unwrap()).current.id,
+			bou.current_status(Tokyo.with_ymd_and_hms(2021, 11, 15, 5, 59, 59).unwrap()).current.id,
 			"kaiyo"
 		);
 
 		assert_eq!(
-			bou.current_status(Local.with_ymd_and_hms(2021, 11, 15, 5, 0, 0).unwrap()).current.id,
+			bou.current_status(Tokyo.with_ymd_and_hms(2021, 11, 15, 5, 0, 0).unwrap()).current.id,
 			"kaiyo"
 		);
 
 		assert_eq!(
-			bou.current_status(Local.with_ymd_and_hms(2021, 11, 15, 4, 59, 59).unwrap()).current.id,
+			bou.current_status(Tokyo.with_ymd_and_hms(2021, 11, 15, 4, 59, 59).unwrap()).current.id,
 			"all3"
 		);
 		// last of 1st lap
@@ -256,9 +285,9 @@ pub(crate) mod tests {
 
 	}
 
-	pub(crate) fn data<'a>() -> Boueigun<'a> {
+	pub(crate) fn data() -> Boueigun {
 		let inner: BoueigunJson = serde_json::from_str(DATA).unwrap();
-		let monsters = BoueigunMonsters::new(&inner.monsters).unwrap();
+		let monsters = BoueigunMonsters::new(&inner.monsters, &Monsters::load().unwrap()).unwrap();
 		let total_duration = monsters.iter()
 			.fold(0, |acc, m| acc + m.duration);
 		Boueigun {
@@ -1,53 +1,112 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ Datelike, DateTime, Duration, Local };
+use std::sync::Arc;
+use chrono::{ Datelike, DateTime, Duration };
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
-	monsters::Monster,
+	config,
+	monsters::{ Monster, Monsters },
 	resistances::Resistances,
-	utils::transform_string_to_regex,
+	utils::{
+		transform_string_to_datetime,
+		transform_string_to_regex,
+	},
+	validation::Validator,
 };
 use super::{
+	Announcement,
 	Announcer,
 	AnnouncementCriteria,
 	Responder,
+	Response,
 	ResponseCriteria,
+	Template,
 };
 
-const DATA: &str = "drakeema-data/contents/jashin.json";
+const DATA: &str = "contents/jashin.json";
 
 #[derive(Debug, Clone)]
-pub struct Jashin<'a> {
-	tables: Tables<'a>,
+pub struct Jashin {
+	tables: Tables,
 	inner: JashinJson,
 }
 
-impl<'a> Jashin<'a> {
-	pub fn load() -> Result<Self> {
+impl Jashin {
+	pub fn load(monsters: &Monsters) -> Result<Self> {
 		info!("Initialize Jashin");
 
     	let mut inner: JashinJson = serde_json::from_reader(
-    		BufReader::new(File::open(DATA)?)
+    		BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
 		inner.tables.sort_by(|a, b| a.start_day.cmp(&b.start_day));
 
 		Ok(Jashin {
-			tables: Tables::new(&inner.tables, inner.reference_date)?,
+			tables: Tables::new(&inner.tables, inner.reference_date, monsters)?,
 			inner,
 		})
 	}
 
-	fn title(&self, at: DateTime<Local>) -> &Title {
+	fn title(&self, at: DateTime<Tz>) -> &Title {
 		self.tables.table(at).titles.title(at)
 	}
 }
 
-impl<'a> Announcer for Jashin<'a> {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+pub fn validate(validator: &mut Validator, monsters: &HashMap<String, Monster>) {
+	let json: JashinJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	let placeholders = ["__TITLE__", "__MONSTERS__", "__RESISTANCES__"];
+	validator.check_template(DATA, "announcement", &json.announcement, &["__TITLE__"]);
+	validator.check_template(DATA, "announcement_at_start", &json.announcement_at_start, &placeholders);
+	validator.check_template(DATA, "announcement_at_end", &json.announcement_at_end, &["__TITLE1__", "__TITLE2__"]);
+	validator.check_template(DATA, "information", &json.information, &placeholders);
+	validator.check_not_empty(DATA, "tables", &json.tables);
+
+	for (i, table) in json.tables.iter().enumerate() {
+		validator.check_not_empty(DATA, &format!("tables[{}].titles", i), &table.titles);
+
+		for (j, title) in table.titles.iter().enumerate() {
+			let mut num_areas: Vec<usize> = Vec::new();
+
+			for (k, monster_id) in title.monster_ids.iter().enumerate() {
+				let field = format!("tables[{}].titles[{}].monster_ids[{}]", i, j, k);
+				if let Some(monster) = validator.check_monster_id(DATA, &field, monsters, monster_id) {
+					num_areas.push(monster.resistances().len());
+				}
+			}
+
+			// Resistances of monsters are joined by area when there are multiple areas.
+			let field = format!("tables[{}].titles[{}]", i, j);
+			let multi_areas = num_areas.iter().filter(|n| **n > 1).collect::<Vec<&usize>>();
+			if multi_areas.windows(2).any(|w| w[0] != w[1]) {
+				validator.push(DATA, &field, "resistances of monsters have different numbers of areas");
+			}
+			if let Some(max) = multi_areas.iter().max() {
+				if json.area_names.len() < **max {
+					validator.push(DATA, &field, format!(
+						"resistances for {} areas are given but area_names has {} names",
+						max, json.area_names.len()
+					));
+				}
+			}
+		}
+	}
+}
+
+impl Announcer for Jashin {
+	fn name(&self) -> &'static str {
+		"jashin"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		use std::ops::Add;
 
 		debug!("Start building announce about Jashin: {:?}", criteria);
@@ -57,40 +116,45 @@ impl<'a> Announcer for Jashin<'a> {
 		let title_yesterday = self.title(criteria.at().add(Duration::hours(-24)));
 
 		let announcement = if title_today != title_yesterday {
-			// Date is start date of the period
-			self.announcement_at_start
-				.replace("__TITLE__", title_today.display_title())
-				.replace("__MONSTERS__", title_today.display_monsters().as_str())
-				.replace("__RESISTANCES__", title_today.display_resistances(Some(&self.area_names)).as_str())
+			// Date is start date of the period, which is long enough to be posted separately
+			self.announcement_at_start.announce(&[
+				("__TITLE__", title_today.display_title()),
+				("__MONSTERS__", title_today.display_monsters().as_str()),
+				("__RESISTANCES__", title_today.display_resistances(Some(&self.area_names)).as_str()),
+			])
+			.separately()
 		} else if title_today != title_tomorrow {
 			// Date is end date of period
-			self.announcement_at_end
-				.replace("__TITLE1__", title_today.display_title())
-				.replace("__TITLE2__", title_tomorrow.display_title())
+			self.announcement_at_end.announce(&[
+				("__TITLE1__", title_today.display_title()),
+				("__TITLE2__", title_tomorrow.display_title()),
+			])
 		} else {
 			// Date is duaring the period
-			self.announcement
-				.replace("__TITLE__", title_today.display_title())
+			self.announcement.announce(&[
+				("__TITLE__", title_today.display_title()),
+			])
 		};
 
 		Some(announcement)
 	}
 }
 
-impl<'a> Responder for Jashin<'a> {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+impl Responder for Jashin {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		debug!("Start building response about Jashin: {:?}", criteria);
 
 		if self.nickname_regex.is_match(criteria.text()) {
 			info!("Text matched keywords of Jashin: {}", criteria.text());
 
 			let title = self.title(criteria.at());
-			let response = self.information
-    			.replace("__TITLE__", title.display_title())
-    			.replace("__MONSTERS__", title.display_monsters().as_str())
-				.replace("__RESISTANCES__", title.display_resistances(Some(&self.area_names)).as_str());
+			let response = self.information.render(&[
+				("__TITLE__", title.display_title()),
+				("__MONSTERS__", title.display_monsters().as_str()),
+				("__RESISTANCES__", title.display_resistances(Some(&self.area_names)).as_str()),
+			]);
 			
-			Some(response)
+			Some(Response::from(response))
 		} else {
 			debug!("Nothing response about jashin: {:?}", criteria);
 			None
@@ -98,7 +162,7 @@ impl<'a> Responder for Jashin<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for Jashin<'a> {
+impl std::ops::Deref for Jashin {
 	type Target = JashinJson;
 
 	fn deref(&self) -> &Self::Target {
@@ -107,21 +171,23 @@ impl<'a> std::ops::Deref for Jashin<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct Tables<'a> {
-	reference_date: DateTime<Local>,
-	inner: Vec<Table<'a>>,
+struct Tables {
+	reference_date: DateTime<Tz>,
+	inner: Vec<Table>,
 }
 
-impl<'a> Tables<'a> {
+impl Tables {
 	fn new(
 		tables: impl AsRef<[TableJson]>,
-		reference_date: DateTime<Local>) -> Result<Self> {
+		reference_date: DateTime<Tz>,
+		monsters: &Monsters,
+	) -> Result<Self> {
 		let mut inner: Vec<Table> = Vec::new();
 
 		for table in tables.as_ref() {
 			inner.push(Table {
 				start_day: table.start_day,
-				titles: Titles::new(&table.titles, reference_date)?
+				titles: Titles::new(&table.titles, reference_date, monsters)?
 			})
 		}
 
@@ -135,7 +201,7 @@ impl<'a> Tables<'a> {
 		}
 	}
 
-	fn table(&self, at: DateTime<Local>) -> &Table {
+	fn table(&self, at: DateTime<Tz>) -> &Table {
 		self.iter()
 			.rev()
 			.find(|table| {
@@ -149,8 +215,8 @@ impl<'a> Tables<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for Tables<'a> {
-	type Target = Vec<Table<'a>>;
+impl std::ops::Deref for Tables {
+	type Target = Vec<Table>;
 
 	fn deref(&self) -> &Self::Target {
 		&self.inner
@@ -158,31 +224,31 @@ impl<'a> std::ops::Deref for Tables<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct Table<'a> {
+struct Table {
 	start_day: u32,
-	titles: Titles<'a>
+	titles: Titles
 }
 
 #[derive(Debug, Clone)]
-struct Titles<'a> {
-	reference_date: DateTime<Local>,
-	inner: Vec<Title<'a>>,
+struct Titles {
+	reference_date: DateTime<Tz>,
+	inner: Vec<Title>,
 }
 
-impl<'a> Titles<'a> {
+impl Titles {
 	fn new(
 		titles: impl AsRef<[TitleJson]>,
-		reference_date: DateTime<Local>
+		reference_date: DateTime<Tz>,
+		monsters: &Monsters,
 	) -> Result<Self> {
-		let mut inner: Vec<Title<'a>> = Vec::new();
-		let monsters = crate::monsters();
+		let mut inner: Vec<Title> = Vec::new();
 
 		for title in titles.as_ref() {
-			let mut mon: Vec<&'a Monster> = Vec::new();
+			let mut mon: Vec<Arc<Monster>> = Vec::new();
 
 			for monster_id in title.monster_ids.iter() {
 				match monsters.get(monster_id) {
-					Some(monster) => mon.push(monster),
+					Some(monster) => mon.push(Arc::clone(monster)),
 					None => return Err(
 						Error::UnknownMonsterId(DATA, monster_id.to_owned())
 					)
@@ -208,7 +274,7 @@ impl<'a> Titles<'a> {
 		}
 	}
 
-	fn title(&self, at: DateTime<Local>) -> &Title {
+	fn title(&self, at: DateTime<Tz>) -> &Title {
 		let elapsed_months = self.elapsed_months(at);
 		let elapsed_months = if elapsed_months < 0i32 {
 			let len = self.len() as i32;
@@ -221,7 +287,7 @@ impl<'a> Titles<'a> {
 		self.get(elapsed_months).unwrap()
 	}
 
-	fn elapsed_months(&self, at: DateTime<Local>) -> i32 {
+	fn elapsed_months(&self, at: DateTime<Tz>) -> i32 {
 		let mut elapsed_months =
 			(at.year() - self.reference_date.year()) * 12 +
 			(at.month() as i32 - self.reference_date.month() as i32);
@@ -244,8 +310,8 @@ impl<'a> Titles<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for Titles<'a> {
-	type Target = Vec<Title<'a>>;
+impl std::ops::Deref for Titles {
+	type Target = Vec<Title>;
 
 	fn deref(&self) -> &Self::Target {
 		&self.inner
@@ -253,13 +319,13 @@ impl<'a> std::ops::Deref for Titles<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct Title<'a> {
+struct Title {
 	id: String,
 	display: String,
-	monsters: Vec<&'a Monster>,
+	monsters: Vec<Arc<Monster>>,
 }
 
-impl<'a> Title<'a> {
+impl Title {
 	fn display_title(&self) -> &str {
 		self.display.as_str()
 	}
@@ -285,21 +351,21 @@ impl<'a> Title<'a> {
 
 use std::cmp;
 
-impl<'a> cmp::PartialEq for Title<'a> {
+impl cmp::PartialEq for Title {
 	fn eq(&self, other: &Self) -> bool {
 		self.id == other.id
 	}
 }
 
-impl<'a> cmp::PartialOrd for Title<'a> {
+impl cmp::PartialOrd for Title {
 	fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
 		Some(self.cmp(other))
 	}
 }
 
-impl<'a> cmp::Eq for Title<'a> {}
+impl cmp::Eq for Title {}
 
-impl<'a> cmp::Ord for Title<'a> {
+impl cmp::Ord for Title {
 	fn cmp(&self, other: &Self) -> cmp::Ordering {
 		self.id.cmp(&other.id)
 	}
@@ -307,7 +373,7 @@ impl<'a> cmp::Ord for Title<'a> {
 
 use std::hash;
 
-impl<'a> hash::Hash for Title<'a> {
+impl hash::Hash for Title {
 	fn hash<H: hash::Hasher>(&self, state: &mut H) {
 		self.id.hash(state);
 	}
@@ -315,12 +381,13 @@ impl<'a> hash::Hash for Title<'a> {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct JashinJson {
-	reference_date: DateTime<Local>,
+	#[serde(deserialize_with = "transform_string_to_datetime")]
+	reference_date: DateTime<Tz>,
 	area_names: Vec<String>,
-	announcement: String,
-	announcement_at_start: String,
-	announcement_at_end: String,
-	information: String,
+	announcement: Template,
+	announcement_at_start: Template,
+	announcement_at_end: Template,
+	information: Template,
 
 	#[serde(deserialize_with = "transform_string_to_regex")]
 	nickname_regex: regex::Regex,
@@ -344,6 +411,7 @@ struct TitleJson {
 pub(crate) mod tests {
 	use super::*;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_title() {
@@ -351,68 +419,68 @@ pub(crate) mod tests {
 
 		// 1st title
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 7, 10, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 7, 10, 6, 0, 0).unwrap()).id,
 			"five_elemental_armors"
 		);
 
 		// Edge of 1st title
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 7, 25, 5, 59, 59).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 7, 25, 5, 59, 59).unwrap()).id,
 			"five_elemental_armors"
 		);
 
 		// 2nd title
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 7, 25, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 7, 25, 6, 0, 0).unwrap()).id,
 			"dream_masako"
 		);
 
 		// Edge of 2nd title
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 8, 10, 5, 59, 59).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 8, 10, 5, 59, 59).unwrap()).id,
 			"dream_masako"
 		);
 
 
 		// 3rd title
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 8, 10, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 8, 10, 6, 0, 0).unwrap()).id,
 			"malucia_thoma"
 		);
 
 		// 4th title
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 8, 25, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 8, 25, 6, 0, 0).unwrap()).id,
 			"malucia_masako"
 		);
 
 		// last title of 1st period of 1st table
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 11, 10, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 11, 10, 6, 0, 0).unwrap()).id,
 			"raz_zel"
 		);
 
 		// 1st title of 2nd period of 1st table
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 12, 10, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 12, 10, 6, 0, 0).unwrap()).id,
 			"five_elemental_armors"
 		);
 
 		// 6th title of 1st period of 2nd table
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 12, 25, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 12, 25, 6, 0, 0).unwrap()).id,
 			"zel_masako"
 		);
 
 		// Last title of 1st period of 2nd table
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2021, 4, 25, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2021, 4, 25, 6, 0, 0).unwrap()).id,
 			"calamity_malucia"
 		);
 
 		// 1st title of 2nd period of 2nd table
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2021, 5, 25, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2021, 5, 25, 6, 0, 0).unwrap()).id,
 			"dream_masako"
 		);
 	}
@@ -423,28 +491,28 @@ pub(crate) mod tests {
 
 		// Edge of title before reference date
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 7, 10, 5, 59, 59).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 7, 10, 5, 59, 59).unwrap()).id,
 			"calamity_malucia"
 		);
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 6, 25, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 6, 25, 6, 0, 0).unwrap()).id,
 			"calamity_malucia"
 		);
 
 		// 2 titles before the reference date
 		assert_eq!(
-			jashin.title(Local.with_ymd_and_hms(2020, 6, 10, 6, 0, 0).unwrap()).id,
+			jashin.title(Tokyo.with_ymd_and_hms(2020, 6, 10, 6, 0, 0).unwrap()).id,
 			"raz_zel"
 		);
 	}
 
-	pub(crate) fn data<'a>() -> Jashin<'a> {
+	pub(crate) fn data() -> Jashin {
 		let mut inner: JashinJson = serde_json::from_str(TEST_DATA).unwrap();
 
 		inner.tables.sort_by(|a, b| a.start_day.cmp(&b.start_day));
 
 		Jashin {
-			tables: Tables::new(&inner.tables, inner.reference_date).unwrap(),
+			tables: Tables::new(&inner.tables, inner.reference_date, &Monsters::load().unwrap()).unwrap(),
 			inner,
 		}
 	}
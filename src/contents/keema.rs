@@ -4,11 +4,13 @@ use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
 };
-use super::{ Responder, ResponseCriteria };
+use super::{ Responder, Response, ResponseCriteria, Text };
 use crate::utils::transform_string_to_regex;
+use crate::validation::Validator;
 
-const DATA: &str = "drakeema-data/contents/keema.json";
+const DATA: &str = "contents/keema.json";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Keema {
@@ -21,15 +23,26 @@ impl Keema {
 
 		Ok(Keema {
 			keywords: serde_json::from_reader(
-				BufReader::new(File::open(DATA)?)
+				BufReader::new(File::open(config::data_path(DATA))?)
 			)
 			.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?
 		})
     }
 }
 
+pub fn validate(validator: &mut Validator) {
+	let keywords: Vec<Keyword> = match validator.parse(DATA) {
+		Some(keywords) => keywords,
+		None => return,
+	};
+
+	for (i, keyword) in keywords.iter().enumerate() {
+		validator.check_not_empty(DATA, &format!("[{}].responses", i), &keyword.responses);
+	}
+}
+
 impl Responder for Keema {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		use chrono::Timelike;
 
 		debug!("Start building response from Keema: {:?}", criteria);
@@ -42,7 +55,9 @@ impl Responder for Keema {
 				)
 				.unwrap()
 				.to_owned()
-			});
+			})
+			.map(Text::from)
+			.map(Response::from);
 		
 		if response.is_some() {
 			info!("Text matched keywords of Keema: {}", criteria.text());
@@ -62,18 +77,17 @@ struct Keyword {
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use chrono::Local;
 
 	#[test]
 	fn test_is_match() {
 		let keema = data();
-		assert!(keema.respond(&ResponseCriteria::new(Local::now(), "簡単なこと")).is_some());
+		assert!(keema.respond(&ResponseCriteria::new(crate::timezone::now(), "簡単なこと")).is_some());
 	}
 
 	#[test]
 	fn test_is_not_match() {
 		let keema = data();
-		assert!(keema.respond(&ResponseCriteria::new(Local::now(), "あいうえお")).is_none());
+		assert!(keema.respond(&ResponseCriteria::new(crate::timezone::now(), "あいうえお")).is_none());
 	}
 
 	pub(crate) fn data() -> Keema {
@@ -1,58 +1,91 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
+use std::sync::Arc;
 use chrono:: {
 	Datelike,
 	DateTime,
 	Duration,
-	Local,
 	LocalResult,
 	Timelike,
 	TimeZone
 };
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
-	monsters::Monster,
+	monsters::{ Monster, Monsters },
 	Result,
-	utils::transform_string_to_regex,
+	config,
+	utils::{
+		transform_string_to_datetime,
+		transform_string_to_regex,
+	},
+	validation::Validator,
 };
 use super::{
+	Announcement,
 	Announcer,
 	AnnouncementCriteria,
 	Responder,
+	Response,
 	ResponseCriteria,
+	Template,
 };
 
-const DATA: &str = "drakeema-data/contents/konmeiko.json";
+const DATA: &str = "contents/konmeiko.json";
+/// Hour in the timezone of the game when each term starts.
 const START_TIME: u32 = 6;
 
 #[derive(Debug, Clone)]
-pub struct Konmeiko<'a> {
-	monsters: KonmeikoMonsters<'a>,
+pub struct Konmeiko {
+	monsters: KonmeikoMonsters,
 	inner: KonmeikoJson,
 }
 
-impl<'a> Konmeiko<'a> {
+pub fn validate(validator: &mut Validator, monsters: &HashMap<String, Monster>) {
+	let json: KonmeikoJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	let placeholders = ["__MONSTERS__", "__RESISTANCES__", "__END_OF_TERM__"];
+	validator.check_template(DATA, "announcement", &json.announcement, &["__MONSTERS__", "__END_OF_TERM__"]);
+	validator.check_template(DATA, "announcement_at_start", &json.announcement_at_start, &placeholders);
+	validator.check_template(DATA, "information", &json.information, &placeholders);
+	validator.check_template(DATA, "out_of_term", &json.out_of_term, &[]);
+	validator.check_not_empty(DATA, "days", &json.days);
+	validator.check_not_empty(DATA, "monsters", &json.monsters);
+
+	for (i, monster) in json.monsters.iter().enumerate() {
+		let field = format!("monsters[{}].monster_id", i);
+		if let Some(m) = validator.check_monster_id(DATA, &field, monsters, &monster.monster_id) {
+			validator.check_single_area(DATA, &field, m);
+		}
+	}
+}
+
+impl Konmeiko {
 
-	pub fn load() -> Result<Self> {
+	pub fn load(monsters: &Monsters) -> Result<Self> {
 		info!("Initialize Konmeiko");
 
 		let inner: KonmeikoJson = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
 		Ok(Konmeiko {
-			monsters: KonmeikoMonsters::new(&inner.monsters)?,
+			monsters: KonmeikoMonsters::new(&inner.monsters, monsters)?,
 			inner,
 		})
 	}
 
-	fn event_status(&self, at: DateTime<Local>) -> EventStatus {
+	fn event_status(&self, at: DateTime<Tz>) -> EventStatus {
 		let term_in_hours: Duration = Duration::hours(self.term_in_hours);
 
 		for d in self.days.iter() {
-			let term_start: DateTime<Local> = match at.timezone().with_ymd_and_hms(
+			let term_start: DateTime<Tz> = match at.timezone().with_ymd_and_hms(
 				at.year(),
 				at.month(),
 				d.to_owned(),
@@ -68,7 +101,7 @@ impl<'a> Konmeiko<'a> {
 
 			};
 
-			let term_end: DateTime<Local> = match term_start.checked_add_signed(term_in_hours) {
+			let term_end: DateTime<Tz> = match term_start.checked_add_signed(term_in_hours) {
 				Some(te) => te,
 				None => {
 					error!("DateTime addition failed");
@@ -98,7 +131,7 @@ impl<'a> Konmeiko<'a> {
 		self.nickname_regex.is_match(text.as_ref())
 	}
 
-	fn current_monster(&self, at: DateTime<Local>) -> &KonmeikoMonster {
+	fn current_monster(&self, at: DateTime<Tz>) -> &KonmeikoMonster {
 		let index = if self.reference_date.year() == at.year() {
 			(
 				// number of past month's switching
@@ -123,59 +156,65 @@ impl<'a> Konmeiko<'a> {
 	}
 }
 
-impl<'a> Announcer for Konmeiko<'a> {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+impl Announcer for Konmeiko {
+	fn name(&self) -> &'static str {
+		"konmeiko"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announcement about Konmeiko: {:?}", criteria);
 
 		let monster = self.current_monster(criteria.at());
 		match self.event_status(criteria.at()) {
 			EventStatus::StartOfTerm{ start: _, end } => Some(
-				self.announcement_at_start
-					.replace("__MONSTERS__", monster.display())
-					.replace("__RESISTANCES__", monster.resistances().display(None::<Vec<String>>).as_ref())
-					.replace("__END_OF_TERM__", format!(
+				self.announcement_at_start.announce(&[
+					("__MONSTERS__", monster.display()),
+					("__RESISTANCES__", monster.resistances().display(None::<Vec<String>>).as_ref()),
+					("__END_OF_TERM__", format!(
 						"{}年{}月{}日の{}時", end.year(), end.month(), end.day(), end.hour()
-					).as_str())
-
+					).as_str()),
+				])
 			),
 			EventStatus::OnTerm{ start: _, end } => Some(
-				self.announcement
-					.replace("__MONSTERS__", monster.display())
-					.replace( "__END_OF_TERM__", format!(
+				self.announcement.announce(&[
+					("__MONSTERS__", monster.display()),
+					("__END_OF_TERM__", format!(
 						"{}年{}月{}日の{}時", end.year(), end.month(), end.day(), end.hour()
-					).as_str())
+					).as_str()),
+				])
 			),
 			EventStatus::OutOfTerm => None
 		}
 	}
 }
 
-impl<'a> Responder for Konmeiko<'a> {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+impl Responder for Konmeiko {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		debug!("start to reaction about Konmeiko: {}", criteria.text());
 
 		if self.is_match(criteria.text()) {
-			match self.event_status(criteria.at()) {
+			let response = match self.event_status(criteria.at()) {
 				EventStatus::OutOfTerm => {
-					Some(self.out_of_term.to_owned())
+					self.out_of_term.render(&[])
 				},
 				EventStatus::StartOfTerm {start: _, end } |
 				EventStatus::OnTerm {start: _, end } =>
-					Some(self.information
-						.replace("__MONSTERS__", self.current_monster(criteria.at()).display())
-						.replace("__RESISTANCES__", self.current_monster(criteria.at()).resistances().display(None::<Vec<String>>).as_ref())
-						.replace( "__END_OF_TERM__", format!(
+					self.information.render(&[
+						("__MONSTERS__", self.current_monster(criteria.at()).display()),
+						("__RESISTANCES__", self.current_monster(criteria.at()).resistances().display(None::<Vec<String>>).as_ref()),
+						("__END_OF_TERM__", format!(
 							"{}年{}月{}日の{}時", end.year(), end.month(), end.day(), end.hour()
-						).as_str())
-					)
-			}
+						).as_str()),
+					]),
+			};
+			Some(Response::from(response))
 		} else {
 			None
 		}
 	}
 }
 
-impl<'a> std::ops::Deref for Konmeiko<'a> {
+impl std::ops::Deref for Konmeiko {
 	type Target = KonmeikoJson;
 
 	fn deref(&self) -> &Self::Target {
@@ -186,23 +225,24 @@ impl<'a> std::ops::Deref for Konmeiko<'a> {
 #[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 enum EventStatus {
 	StartOfTerm {
-		start: DateTime<Local>,
-		end: DateTime<Local>
+		start: DateTime<Tz>,
+		end: DateTime<Tz>
 	},
 	OnTerm {
-		start: DateTime<Local>,
-		end: DateTime<Local>
+		start: DateTime<Tz>,
+		end: DateTime<Tz>
 	},
 	OutOfTerm,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct KonmeikoJson {
-	reference_date: DateTime<Local>,
-	announcement: String,
-	announcement_at_start: String,
-	information: String,
-	out_of_term: String,
+	#[serde(deserialize_with = "transform_string_to_datetime")]
+	reference_date: DateTime<Tz>,
+	announcement: Template,
+	announcement_at_start: Template,
+	information: Template,
+	out_of_term: Template,
 	#[serde(deserialize_with = "transform_string_to_regex")]
 	nickname_regex: regex::Regex,
 	days: Vec<u32>,
@@ -211,20 +251,19 @@ pub struct KonmeikoJson {
 }
 
 #[derive(Debug, Clone)]
-struct KonmeikoMonsters<'a> {
-	inner: Vec<KonmeikoMonster<'a>>,
+struct KonmeikoMonsters {
+	inner: Vec<KonmeikoMonster>,
 }
 
-impl<'a> KonmeikoMonsters<'a> {
-	fn new(k_monsters: impl AsRef<[MonsterJson]>) -> Result<Self> {
-		let mut inner: Vec<KonmeikoMonster<'a>> = Vec::new();
-		let monsters = crate::monsters();
+impl KonmeikoMonsters {
+	fn new(k_monsters: impl AsRef<[MonsterJson]>, monsters: &Monsters) -> Result<Self> {
+		let mut inner: Vec<KonmeikoMonster> = Vec::new();
 
 		for monster in k_monsters.as_ref() {
 			match monsters.get(&monster.monster_id) {
 				Some(m) => inner.push(KonmeikoMonster {
 					id: monster.id.to_owned(),
-					monster: m,
+					monster: Arc::clone(m),
 				}),
 				None => return Err(
 					Error::UnknownMonsterId(DATA, monster.monster_id.clone())
@@ -238,8 +277,8 @@ impl<'a> KonmeikoMonsters<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for KonmeikoMonsters<'a> {
-	type Target = Vec<KonmeikoMonster<'a>>;
+impl std::ops::Deref for KonmeikoMonsters {
+	type Target = Vec<KonmeikoMonster>;
 
 	fn deref(&self) -> &Self::Target {
 		&self.inner
@@ -247,26 +286,26 @@ impl<'a> std::ops::Deref for KonmeikoMonsters<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct KonmeikoMonster<'a> {
+struct KonmeikoMonster {
 	#[allow(dead_code)]
 	id: String,
-	monster: &'a Monster,
+	monster: Arc<Monster>,
 }
 
-impl<'a> KonmeikoMonster<'a> {
+impl KonmeikoMonster {
 	#[allow(dead_code)]
 	pub fn id(&self) -> &str {
 		&self.id
 	}
 
 	#[allow(dead_code)]
-	pub fn monster(&self) -> &'a Monster {
+	pub fn monster(&self) -> &Monster {
 		&self.monster
 	}
 }
 
-impl<'a> std::ops::Deref for KonmeikoMonster<'a> {
-	type Target = &'a Monster;
+impl std::ops::Deref for KonmeikoMonster {
+	type Target = Monster;
 
 	fn deref(&self) -> &Self::Target {
 		&self.monster
@@ -282,112 +321,112 @@ struct MonsterJson {
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use chrono::Local;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_event_status() {
 		let kmk = data(1);
 
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 01,
 				6, 0, 0).unwrap()
 			), EventStatus::StartOfTerm {
-				start: Local.with_ymd_and_hms(
+				start: Tokyo.with_ymd_and_hms(
 					2024, 07, 01,
 					6, 0, 0).unwrap(),
-				end: Local.with_ymd_and_hms(
+				end: Tokyo.with_ymd_and_hms(
 					2024, 07, 06,
 					6, 0, 0).unwrap(),
 			}
 		);
 
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 02,
 				9, 0, 0).unwrap()
 			), EventStatus::OnTerm {
-				start: Local.with_ymd_and_hms(
+				start: Tokyo.with_ymd_and_hms(
 					2024, 07, 01,
 					6, 0, 0).unwrap(),
-				end: Local.with_ymd_and_hms(
+				end: Tokyo.with_ymd_and_hms(
 					2024, 07, 06,
 					6, 0, 0).unwrap(),
 			}
 		);
 		
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 06,
 				5, 59, 59).unwrap()
 			), EventStatus::OnTerm {
-				start: Local.with_ymd_and_hms(
+				start: Tokyo.with_ymd_and_hms(
 					2024, 07, 01,
 					6, 0, 0).unwrap(),
-				end: Local.with_ymd_and_hms(
+				end: Tokyo.with_ymd_and_hms(
 					2024, 07, 06,
 					6, 0, 0).unwrap(),
 			}
 		);
 		
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 06,
 				6, 0, 0).unwrap()
 			), EventStatus::OutOfTerm
 		);
 
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 10,
 				13, 0, 0).unwrap()
 			), EventStatus::OutOfTerm
 		);
 
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 15,
 				6, 0, 0).unwrap()
 			), EventStatus::StartOfTerm {
-				start: Local.with_ymd_and_hms(
+				start: Tokyo.with_ymd_and_hms(
 					2024, 07, 15,
 					6, 0, 0).unwrap(),
-				end: Local.with_ymd_and_hms(
+				end: Tokyo.with_ymd_and_hms(
 					2024, 07, 20,
 					6, 0, 0).unwrap(),
 			}
 		);
 
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 16,
 				9, 0, 0).unwrap()
 			), EventStatus::OnTerm {
-				start: Local.with_ymd_and_hms(
+				start: Tokyo.with_ymd_and_hms(
 					2024, 07, 15,
 					6, 0, 0).unwrap(),
-				end: Local.with_ymd_and_hms(
+				end: Tokyo.with_ymd_and_hms(
 					2024, 07, 20,
 					6, 0, 0).unwrap(),
 			}
 		);
 		
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 20,
 				5, 59, 59).unwrap()
 			), EventStatus::OnTerm {
-				start: Local.with_ymd_and_hms(
+				start: Tokyo.with_ymd_and_hms(
 					2024, 07, 15,
 					6, 0, 0).unwrap(),
-				end: Local.with_ymd_and_hms(
+				end: Tokyo.with_ymd_and_hms(
 					2024, 07, 20,
 					6, 0, 0).unwrap(),
 			}
 		);
 		
 		assert_eq!(
-			kmk.event_status(Local.with_ymd_and_hms(
+			kmk.event_status(Tokyo.with_ymd_and_hms(
 				2024, 07, 20,
 				6, 0, 0).unwrap()
 			), EventStatus::OutOfTerm
@@ -399,28 +438,28 @@ pub(crate) mod tests {
 		let kmk = data(3);
 
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2024, 07, 1,
 				6, 0, 0).unwrap()).id()
 			, "test1"
 		);
 
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2024, 07, 15,
 				6, 0, 0).unwrap()).id()
 			, "test2"
 		);
 
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2024, 08, 1,
 				6, 0, 0).unwrap()).id()
 			, "test3"
 		);
 
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2024, 08, 15,
 				6, 0, 0).unwrap()).id()
 			, "test1"
@@ -428,7 +467,7 @@ pub(crate) mod tests {
 
 		// 6 months * 2 + 1
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2025, 1, 1,
 				6, 0, 0).unwrap()).id()
 			, "test1"
@@ -436,7 +475,7 @@ pub(crate) mod tests {
 
 		// 8 months * 2 + 1
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2025, 3, 1,
 				6, 0, 0).unwrap()).id()
 			, "test2"
@@ -445,7 +484,7 @@ pub(crate) mod tests {
 		// (12 months + 5 months) * 2 + 1
 		// 35 mod 3: 2
 		assert_eq!(
-			kmk.current_monster(Local.with_ymd_and_hms(
+			kmk.current_monster(Tokyo.with_ymd_and_hms(
 				2026, 4, 1,
 				6, 0, 0).unwrap()).id()
 			, "test3"
@@ -457,42 +496,42 @@ pub(crate) mod tests {
 		let kmk: Konmeiko = data(1);
 
 		assert_eq!(
-			kmk.announce(&AnnouncementCriteria::new(Local.with_ymd_and_hms(
+			kmk.announce(&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(
 				2024, 7, 1,
-				6, 0, 0).unwrap())).unwrap(),
-			"昏冥庫パニガルムが開放されました！2024年7月6日の6時まで 冥氷竜ジェロドーラ と戦えます！呪文、おびえ、氷、闇の耐性があると良いようです！".to_string()
+				6, 0, 0).unwrap())).unwrap().text(),
+			"昏冥庫パニガルムが開放されました！2024年7月6日の6時まで 冥氷竜ジェロドーラ と戦えます！呪文、おびえ、氷、闇の耐性があると良いようです！"
 		);
 
 		assert_eq!(
-			kmk.announce(&AnnouncementCriteria::new(Local.with_ymd_and_hms(
+			kmk.announce(&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(
 				2024, 7, 6,
-				5, 59, 59).unwrap())).unwrap(),
+				5, 59, 59).unwrap())).unwrap().text(),
 			"本日の昏冥庫パニガルムは 冥氷竜ジェロドーラ です！2024年7月6日の6時まで開放されています！",
 		);
 
 		assert_eq!(
-			kmk.announce(&AnnouncementCriteria::new(Local.with_ymd_and_hms(
+			kmk.announce(&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(
 				2024, 7, 6,
 				6, 0, 0).unwrap())),
 			None,
 		);
 
 		assert_eq!(
-			kmk.respond(&ResponseCriteria::new(Local.with_ymd_and_hms(
+			kmk.respond(&ResponseCriteria::new(Tokyo.with_ymd_and_hms(
 				2024, 7, 15,
-				6, 0, 0).unwrap(), "こんめーこ")).unwrap(),
+				6, 0, 0).unwrap(), "こんめーこ")).unwrap().text(),
 			"本日の昏冥庫パニガルムは 冥氷竜ジェロドーラ です！2024年7月20日の6時まで開放されています！呪文、おびえ、氷、闇の耐性があると良いようです！",
 		);
 
 		assert_eq!(
-			kmk.respond(&ResponseCriteria::new(Local.with_ymd_and_hms(
+			kmk.respond(&ResponseCriteria::new(Tokyo.with_ymd_and_hms(
 				2024, 7, 20,
-				6, 0, 0).unwrap(), "こんめーこ")).unwrap(),
+				6, 0, 0).unwrap(), "こんめーこ")).unwrap().text(),
 			"本日の昏冥庫パニガルムは開いてません！",
 		);
 	}
 
-	pub(crate) fn data<'a>(num: u8) -> Konmeiko<'a> {
+	pub(crate) fn data(num: u8) -> Konmeiko {
 		let inner: &str = match num {
 			1 => TEST_DATA1,
 			2 => TEST_DATA2,
@@ -502,7 +541,7 @@ pub(crate) mod tests {
 
 		let inner: KonmeikoJson = serde_json::from_str(inner).unwrap();
 		Konmeiko {
-			monsters: KonmeikoMonsters::new(&inner.monsters).unwrap(),
+			monsters: KonmeikoMonsters::new(&inner.monsters, &Monsters::load().unwrap()).unwrap(),
 			inner,
 		}
 	}
@@ -6,8 +6,10 @@ pub(crate) mod konmeiko;
 pub(crate) mod monthly_contents;
 pub(crate) mod panigulm;
 pub(crate) mod periodic_contents;
+pub(crate) mod registry;
 pub(crate) mod seishugosha;
 pub(crate) mod weekly_activity;
+pub(crate) mod weekly_contents;
 
 pub use boueigun::Boueigun;
 //pub use feed::Feeds;
@@ -19,49 +21,57 @@ pub use panigulm::Panigulm;
 pub use periodic_contents::PeriodicContents;
 pub use seishugosha::Seishugosha;
 pub use weekly_activity::WeeklyActivity;
+pub use weekly_contents::WeeklyContents;
 
-use chrono::{ DateTime, Local };
+use chrono::DateTime;
+use chrono_tz::Tz;
+use mastors::entities::Visibility;
+use serde::Deserialize;
+use crate::message_processor::{ PollOptions, Priority };
 
 pub trait Announcer: Sync + Send {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String>;
+	/// Name of the content, which is written in the log when announcing about it fails.
+	fn name(&self) -> &'static str;
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement>;
 }
 
 #[derive(Debug, Clone)]
 pub struct AnnouncementCriteria {
-	at: DateTime<Local>,
+	at: DateTime<Tz>,
 }
 
 impl AnnouncementCriteria {
-	pub fn new(at: DateTime<Local>) -> Self {
+	pub fn new(at: DateTime<Tz>) -> Self {
 		AnnouncementCriteria {
 			at,
 		}
 	}
 
-	pub fn at(&self) -> DateTime<Local> {
+	pub fn at(&self) -> DateTime<Tz> {
 		self.at
 	}
 }
 
 pub trait Responder: Sync + Send {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String>;
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response>;
 }
 
 #[derive(Debug, Clone)]
 pub struct ResponseCriteria {
-	at: DateTime<Local>,
+	at: DateTime<Tz>,
 	text: String,
 }
 
 impl ResponseCriteria {
-	pub fn new(at: DateTime<Local>, text: impl Into<String>) -> Self {
+	pub fn new(at: DateTime<Tz>, text: impl Into<String>) -> Self {
 		ResponseCriteria {
 			at,
 			text: text.into(),
 		}
 	}
 
-	pub fn at(&self) -> DateTime<Local> {
+	pub fn at(&self) -> DateTime<Tz> {
 		self.at
 	}
 
@@ -69,3 +79,536 @@ impl ResponseCriteria {
 		&self.text
 	}
 }
+
+/// Text to post with the options of the status.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Text {
+	text: String,
+	spoiler_text: Option<String>,
+	language: Option<String>,
+}
+
+impl Text {
+	pub fn new(text: impl Into<String>) -> Self {
+		Text {
+			text: text.into(),
+			spoiler_text: None,
+			language: None,
+		}
+	}
+
+	/// Join texts with the separator.
+	/// Spoiler texts are joined if any, and the first language found is used.
+	pub fn join(texts: impl IntoIterator<Item = Text>, separator: &str) -> Option<Self> {
+		let texts = texts.into_iter().collect::<Vec<Text>>();
+		if texts.is_empty() {
+			return None;
+		}
+
+		let mut spoiler_texts: Vec<&str> = Vec::new();
+		for spoiler_text in texts.iter().filter_map(|t| t.spoiler_text()) {
+			if !spoiler_texts.contains(&spoiler_text) {
+				spoiler_texts.push(spoiler_text);
+			}
+		}
+
+		Some(Text {
+			text: texts.iter()
+				.map(|t| t.text())
+				.collect::<Vec<&str>>()
+				.join(separator),
+			spoiler_text: if spoiler_texts.is_empty() {
+				None
+			} else {
+				Some(spoiler_texts.join(" / "))
+			},
+			language: texts.iter().find_map(|t| t.language.clone()),
+		})
+	}
+
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+
+	pub fn spoiler_text(&self) -> Option<&str> {
+		self.spoiler_text.as_deref()
+	}
+
+	pub fn language(&self) -> Option<&str> {
+		self.language.as_deref()
+	}
+}
+
+impl From<String> for Text {
+	fn from(text: String) -> Self {
+		Text::new(text)
+	}
+}
+
+/// Announcement about a content, which is combined with the others into one status by default.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+	title: Option<String>,
+	body: Text,
+	priority: Priority,
+	visibility: Visibility,
+	hashtags: Vec<String>,
+	separately: bool,
+}
+
+impl Announcement {
+	pub fn new(body: impl Into<Text>) -> Self {
+		Announcement {
+			title: None,
+			body: body.into(),
+			priority: Priority::High,
+			visibility: Visibility::Public,
+			hashtags: Vec::new(),
+			separately: false,
+		}
+	}
+
+	/// Put the title on the first line of the status.
+	pub fn with_title(mut self, title: impl Into<String>) -> Self {
+		self.title = Some(title.into());
+		self
+	}
+
+	pub fn with_priority(mut self, priority: Priority) -> Self {
+		self.priority = priority;
+		self
+	}
+
+	pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+		self.visibility = visibility;
+		self
+	}
+
+	/// Put the hashtags on the last line of the status, `#` is not needed.
+	pub fn with_hashtags(mut self, hashtags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+		self.hashtags.extend(hashtags.into_iter().map(|h| h.into()));
+		self
+	}
+
+	/// Post the announcement as its own status instead of combining it with the others.
+	pub fn separately(mut self) -> Self {
+		self.separately = true;
+		self
+	}
+
+	/// Combine announcements into statuses.
+	/// Announcements to post separately are kept as they are, and the others are combined
+	/// for each visibility and spoiler text with the separator, keeping the order of the first one of each.
+	pub fn combine(announcements: impl IntoIterator<Item = Announcement>, separator: &str) -> Vec<Self> {
+		let mut combined: Vec<Announcement> = Vec::new();
+
+		for announcement in announcements {
+			let group = combined.iter_mut()
+				.find(|a| {
+					!a.separately && !announcement.separately &&
+					a.visibility == announcement.visibility &&
+					a.body.spoiler_text == announcement.body.spoiler_text
+				});
+
+			match group {
+				Some(group) => {
+					// Safe unwrapping because two texts are given.
+					group.body = Text::join(vec![group.render(), announcement.render()], separator).unwrap();
+					group.title = None;
+					group.hashtags = Vec::new();
+					group.priority = std::cmp::max(group.priority, announcement.priority);
+				},
+				None => combined.push(announcement),
+			};
+		}
+
+		combined
+	}
+
+	/// Build the text of the status with the title and the hashtags.
+	pub fn render(&self) -> Text {
+		let mut lines: Vec<String> = Vec::new();
+
+		if let Some(title) = self.title.as_ref() {
+			lines.push(title.to_owned());
+		}
+		lines.push(self.body.text().to_owned());
+		if !self.hashtags.is_empty() {
+			lines.push(
+				self.hashtags.iter()
+					.map(|h| format!("#{}", h.trim_start_matches('#')))
+					.collect::<Vec<String>>()
+					.join(" ")
+			);
+		}
+
+		Text {
+			text: lines.join("\n"),
+			spoiler_text: self.body.spoiler_text.clone(),
+			language: self.body.language.clone(),
+		}
+	}
+
+	pub fn priority(&self) -> Priority {
+		self.priority
+	}
+
+	pub fn visibility(&self) -> Visibility {
+		self.visibility
+	}
+
+	#[cfg(test)]
+	pub fn is_separate(&self) -> bool {
+		self.separately
+	}
+}
+
+impl std::ops::Deref for Announcement {
+	type Target = Text;
+
+	fn deref(&self) -> &Self::Target {
+		&self.body
+	}
+}
+
+impl From<Text> for Announcement {
+	fn from(body: Text) -> Self {
+		Announcement::new(body)
+	}
+}
+
+/// Response to the status, with the options which override the defaults of the reply.
+/// By default the reply mentions the author, is threaded to the status and has the same visibility.
+#[derive(Debug, Clone)]
+pub struct Response {
+	text: Text,
+	visibility: Option<Visibility>,
+	mention: bool,
+	threaded: bool,
+	poll_options: Option<PollOptions>,
+	priority: Option<Priority>,
+}
+
+impl Response {
+	pub fn new(text: impl Into<Text>) -> Self {
+		Response {
+			text: text.into(),
+			visibility: None,
+			mention: true,
+			threaded: true,
+			poll_options: None,
+			priority: None,
+		}
+	}
+
+	pub fn with_visibility(mut self, visibility: Visibility) -> Self {
+		self.visibility = Some(visibility);
+		self
+	}
+
+	/// Post the response without mentioning the author.
+	pub fn without_mention(mut self) -> Self {
+		self.mention = false;
+		self
+	}
+
+	/// Post the response as a new status instead of a reply.
+	pub fn unthreaded(mut self) -> Self {
+		self.threaded = false;
+		self
+	}
+
+	pub fn with_poll_options(mut self, poll_options: PollOptions) -> Self {
+		self.poll_options = Some(poll_options);
+		self
+	}
+
+	pub fn with_priority(mut self, priority: Priority) -> Self {
+		self.priority = Some(priority);
+		self
+	}
+
+	/// Merge responses into one, texts are joined with the separator.
+	/// The first visibility and poll found are used, and the highest priority is used.
+	/// The response mentions and is threaded only when all of the responses do.
+	pub fn merge(responses: impl IntoIterator<Item = Response>, separator: &str) -> Option<Self> {
+		let responses = responses.into_iter().collect::<Vec<Response>>();
+
+		Some(Response {
+			text: Text::join(responses.iter().map(|r| r.text.clone()), separator)?,
+			visibility: responses.iter().find_map(|r| r.visibility),
+			mention: responses.iter().all(|r| r.mention),
+			threaded: responses.iter().all(|r| r.threaded),
+			poll_options: responses.iter().find_map(|r| r.poll_options.clone()),
+			priority: responses.iter().filter_map(|r| r.priority).max(),
+		})
+	}
+
+	pub fn visibility(&self) -> Option<Visibility> {
+		self.visibility
+	}
+
+	pub fn mention(&self) -> bool {
+		self.mention
+	}
+
+	pub fn threaded(&self) -> bool {
+		self.threaded
+	}
+
+	pub fn poll_options(&self) -> Option<&PollOptions> {
+		self.poll_options.as_ref()
+	}
+
+	pub fn priority(&self) -> Option<Priority> {
+		self.priority
+	}
+}
+
+impl std::ops::Deref for Response {
+	type Target = Text;
+
+	fn deref(&self) -> &Self::Target {
+		&self.text
+	}
+}
+
+impl From<Text> for Response {
+	fn from(text: Text) -> Self {
+		Response::new(text)
+	}
+}
+
+/// Template of the text in data files.
+///
+/// The template is written as a string, or as an object which has `text`
+/// and optional `spoiler_text` and `language` of the status.
+/// Templates of announcements can also have optional `title`, `hashtags`, `visibility`,
+/// `priority` and `separately`, which are ignored by responses.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "TemplateJson")]
+pub struct Template {
+	text: String,
+	spoiler_text: Option<String>,
+	language: Option<String>,
+	title: Option<String>,
+	hashtags: Vec<String>,
+	visibility: Option<Visibility>,
+	priority: Option<Priority>,
+	separately: bool,
+}
+
+impl Template {
+	/// Build the text by replacing placeholders in the text and the spoiler text.
+	pub fn render(&self, replacements: &[(&str, &str)]) -> Text {
+		Text {
+			text: replace(&self.text, replacements),
+			spoiler_text: self.spoiler_text.as_deref().map(|s| replace(s, replacements)),
+			language: self.language.clone(),
+		}
+	}
+
+	/// Build the announcement by replacing placeholders in the text and the title,
+	/// with the options of the announcement given in the template.
+	pub fn announce(&self, replacements: &[(&str, &str)]) -> Announcement {
+		let mut announcement = Announcement::new(self.render(replacements))
+			.with_hashtags(self.hashtags.iter().cloned());
+
+		if let Some(title) = self.title.as_deref() {
+			announcement = announcement.with_title(replace(title, replacements));
+		}
+		if let Some(visibility) = self.visibility {
+			announcement = announcement.with_visibility(visibility);
+		}
+		if let Some(priority) = self.priority {
+			announcement = announcement.with_priority(priority);
+		}
+		if self.separately {
+			announcement = announcement.separately();
+		}
+
+		announcement
+	}
+
+	pub fn spoiler_text(&self) -> Option<&str> {
+		self.spoiler_text.as_deref()
+	}
+}
+
+fn replace(s: &str, replacements: &[(&str, &str)]) -> String {
+	replacements.iter()
+		.fold(s.to_owned(), |acc, (from, to)| acc.replace(from, to))
+}
+
+impl std::ops::Deref for Template {
+	type Target = str;
+
+	fn deref(&self) -> &Self::Target {
+		&self.text
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum TemplateJson {
+	Text(String),
+	WithOptions {
+		text: String,
+		#[serde(default)]
+		spoiler_text: Option<String>,
+		#[serde(default)]
+		language: Option<String>,
+		#[serde(default)]
+		title: Option<String>,
+		#[serde(default)]
+		hashtags: Vec<String>,
+		#[serde(default)]
+		visibility: Option<Visibility>,
+		#[serde(default)]
+		priority: Option<Priority>,
+		#[serde(default)]
+		separately: bool,
+	},
+}
+
+impl From<TemplateJson> for Template {
+	fn from(json: TemplateJson) -> Self {
+		match json {
+			TemplateJson::Text(text) => Template {
+				text,
+				spoiler_text: None,
+				language: None,
+				title: None,
+				hashtags: Vec::new(),
+				visibility: None,
+				priority: None,
+				separately: false,
+			},
+			TemplateJson::WithOptions { text, spoiler_text, language, title, hashtags, visibility, priority, separately } => Template {
+				text,
+				spoiler_text,
+				language,
+				title,
+				hashtags,
+				visibility,
+				priority,
+				separately,
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_template_from_string() {
+		let template: Template = serde_json::from_str(r#""本日は __TITLE__ です！""#).unwrap();
+		let text = template.render(&[("__TITLE__", "五属性の災禍")]);
+
+		assert_eq!(text.text(), "本日は 五属性の災禍 です！");
+		assert_eq!(text.spoiler_text(), None);
+		assert_eq!(text.language(), None);
+	}
+
+	#[test]
+	fn test_template_with_options() {
+		let template: Template = serde_json::from_str(r#"{
+			"text": "本日は __TITLE__ です！",
+			"spoiler_text": "__TITLE__ のネタバレ",
+			"language": "ja"
+		}"#).unwrap();
+		let text = template.render(&[("__TITLE__", "五属性の災禍")]);
+
+		assert_eq!(text.text(), "本日は 五属性の災禍 です！");
+		assert_eq!(text.spoiler_text(), Some("五属性の災禍 のネタバレ"));
+		assert_eq!(text.language(), Some("ja"));
+	}
+
+	#[test]
+	fn test_template_with_announcement_options() {
+		let template: Template = serde_json::from_str(r#"{
+			"text": "本日は __TITLE__ です！",
+			"title": "【__TITLE__】",
+			"hashtags": ["ドラクエ10"],
+			"visibility": "unlisted",
+			"priority": "Normal",
+			"separately": true
+		}"#).unwrap();
+		let announcement = template.announce(&[("__TITLE__", "五属性の災禍")]);
+
+		assert_eq!(announcement.render().text(), "【五属性の災禍】\n本日は 五属性の災禍 です！\n#ドラクエ10");
+		assert_eq!(announcement.visibility(), Visibility::Unlisted);
+		assert_eq!(announcement.priority(), Priority::Normal);
+		assert!(announcement.is_separate());
+
+		let template: Template = serde_json::from_str(r#""本日は __TITLE__ です！""#).unwrap();
+		let announcement = template.announce(&[("__TITLE__", "五属性の災禍")]);
+
+		assert_eq!(announcement.render().text(), "本日は 五属性の災禍 です！");
+		assert_eq!(announcement.visibility(), Visibility::Public);
+		assert_eq!(announcement.priority(), Priority::High);
+		assert!(!announcement.is_separate());
+	}
+
+	#[test]
+	fn test_join() {
+		let template: Template = serde_json::from_str(r#"{
+			"text": "ふたつめ",
+			"spoiler_text": "ネタバレ",
+			"language": "ja"
+		}"#).unwrap();
+		let texts = vec![Text::new("ひとつめ"), template.render(&[]), template.render(&[])];
+		let text = Text::join(texts, "\n").unwrap();
+
+		assert_eq!(text.text(), "ひとつめ\nふたつめ\nふたつめ");
+		assert_eq!(text.spoiler_text(), Some("ネタバレ"));
+		assert_eq!(text.language(), Some("ja"));
+
+		assert!(Text::join(Vec::new(), "\n").is_none());
+	}
+
+	#[test]
+	fn test_combine() {
+		let announcements = vec![
+			Announcement::new(Text::new("ひとつめ")).with_title("見出し").with_priority(Priority::Normal),
+			Announcement::new(Text::new("べつ")).separately(),
+			Announcement::new(Text::new("ふたつめ")).with_hashtags(vec!["ドラクエ10"]),
+			Announcement::new(Text::new("みかた")).with_visibility(Visibility::Unlisted),
+			Announcement::new(Text { spoiler_text: Some("ネタバレ".to_owned()), ..Text::new("かくす") }),
+		];
+		let combined = Announcement::combine(announcements, "\n\n");
+
+		assert_eq!(combined.len(), 4);
+		assert_eq!(combined[0].render().text(), "見出し\nひとつめ\n\nふたつめ\n#ドラクエ10");
+		assert_eq!(combined[0].priority(), Priority::High);
+		assert_eq!(combined[1].render().text(), "べつ");
+		assert!(combined[1].is_separate());
+		assert_eq!(combined[2].render().text(), "みかた");
+		assert_eq!(combined[2].visibility(), Visibility::Unlisted);
+		assert_eq!(combined[3].render().text(), "かくす");
+		assert_eq!(combined[3].spoiler_text(), Some("ネタバレ"));
+	}
+
+	#[test]
+	fn test_merge() {
+		let responses = vec![
+			Response::new(Text::new("ひとつめ")).with_priority(Priority::Low),
+			Response::new(Text::new("ふたつめ"))
+				.with_visibility(Visibility::Unlisted)
+				.with_priority(Priority::Normal)
+				.unthreaded(),
+		];
+		let response = Response::merge(responses, "\n").unwrap();
+
+		assert_eq!(response.text(), "ひとつめ\nふたつめ");
+		assert_eq!(response.visibility(), Some(Visibility::Unlisted));
+		assert_eq!(response.priority(), Some(Priority::Normal));
+		assert!(response.mention());
+		assert!(!response.threaded());
+		assert!(response.poll_options().is_none());
+
+		assert!(Response::merge(Vec::new(), "\n").is_none());
+	}
+}
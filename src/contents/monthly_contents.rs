@@ -1,14 +1,16 @@
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ DateTime, Duration, Local };
+use chrono::{ DateTime, Duration };
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
 };
-use super::{ Announcer, AnnouncementCriteria };
+use super::{ Announcement, Announcer, AnnouncementCriteria, Text };
 
-const DATA: &str = "drakeema-data/contents/monthly_contents.json";
+const DATA: &str = "contents/monthly_contents.json";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct MonthlyContents {
@@ -22,21 +24,21 @@ impl MonthlyContents {
 		info!("Initialize MonthlyContents");
 
 		serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))
 	}
 
-	fn contents_to_start(&self, at: &DateTime<Local>) -> String {
+	fn contents_to_start(&self, at: &DateTime<Tz>) -> String {
 		self.contents(at, &self.announcement_at_start)
 	}
 
-	fn contents_to_end(&self, at: &DateTime<Local>) -> String {
+	fn contents_to_end(&self, at: &DateTime<Tz>) -> String {
 		let tomorrow = *at + Duration::days(1);
 		self.contents(&tomorrow, &self.announcement_at_end)
 	}
 
-	fn contents(&self, at: &DateTime<Local>, template: &str) -> String {
+	fn contents(&self, at: &DateTime<Tz>, template: &str) -> String {
 		use chrono::Datelike;
 
 		let contents = self.contents.iter()
@@ -54,7 +56,11 @@ impl MonthlyContents {
 }
 
 impl Announcer for MonthlyContents {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+	fn name(&self) -> &'static str {
+		"monthly_contents"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announce about MonthlyContents: {:?}", criteria);
 
 		let contents = [
@@ -70,7 +76,7 @@ impl Announcer for MonthlyContents {
 			debug!("Nothing announcement about MonthlyContents: {:?}", criteria);
 			None
 		} else {
-			Some(contents)
+			Some(Announcement::from(Text::from(contents)))
 		}
 	}
 }
@@ -87,20 +93,21 @@ struct Content {
 pub(crate) mod tests {
 	use super::*;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_contents_to_start_is_exist() {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 1, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 1, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "今期の :m_nasubimera: シアトリカルクロニクル、不思議の魔塔は今日からです！");
+		assert_eq!(an.unwrap().text(), "今期の :m_nasubimera: シアトリカルクロニクル、不思議の魔塔は今日からです！");
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 15, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 15, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "今期の :m_nasubimera: シアトリカルクロニクルは今日からです！");
+		assert_eq!(an.unwrap().text(), "今期の :m_nasubimera: シアトリカルクロニクルは今日からです！");
 	}
 
 	#[test]
@@ -108,14 +115,14 @@ pub(crate) mod tests {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 31, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 31, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "今期の :m_nasubimera: シアトリカルクロニクル、不思議の魔塔は今日までです！");
+		assert_eq!(an.unwrap().text(), "今期の :m_nasubimera: シアトリカルクロニクル、不思議の魔塔は今日までです！");
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 14, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 14, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "今期の :m_nasubimera: シアトリカルクロニクルは今日までです！");
+		assert_eq!(an.unwrap().text(), "今期の :m_nasubimera: シアトリカルクロニクルは今日までです！");
 	}
 
 	#[test]
@@ -123,7 +130,7 @@ pub(crate) mod tests {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 16, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 16, 12, 0, 0).unwrap())
 		);
 		assert!(an.is_none());
 	}
@@ -1,43 +1,79 @@
 use std::convert::TryFrom;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ DateTime, Duration, Local, };
+use std::sync::Arc;
+use chrono::{ DateTime, Duration, };
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
-	monsters::Monster,
-	utils::transform_string_to_regex,
+	config,
+	monsters::{ Monster, Monsters },
+	utils::{
+		transform_string_to_datetime,
+		transform_string_to_regex,
+	},
+	validation::Validator,
 };
 use super::{
+	Announcement,
 	Announcer,
 	AnnouncementCriteria,
 	Responder,
+	Response,
 	ResponseCriteria,
+	Template,
 };
 
-const DATA: &str = "drakeema-data/contents/panigulm.json";
+const DATA: &str = "contents/panigulm.json";
 
 #[derive(Debug, Clone)]
-pub struct Panigulm<'a> {
-	monsters: Vec<PanigulmMonster<'a>>,
+pub struct Panigulm {
+	monsters: Vec<PanigulmMonster>,
 	inner: PanigulmJson,
 }
 
-impl<'a> Panigulm<'a> {
-	pub fn load() -> Result<Self> {
+pub fn validate(validator: &mut Validator, monsters: &HashMap<String, Monster>) {
+	let json: PanigulmJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	let placeholders = ["__MONSTER__", "__RESISTANCES__"];
+	validator.check_template(DATA, "announcement", &json.announcement, &["__MONSTER__"]);
+	validator.check_template(DATA, "announcement_at_start", &json.announcement_at_start, &placeholders);
+	validator.check_template(DATA, "announcement_at_end", &json.announcement_at_end, &["__MONSTER1__", "__MONSTER2__"]);
+	validator.check_template(DATA, "information", &json.information, &placeholders);
+	validator.check_not_empty(DATA, "monster_ids", &json.monster_ids);
+
+	if json.num_days <= 0 {
+		validator.push(DATA, "num_days", "num_days must be positive");
+	}
+
+	for (i, monster_id) in json.monster_ids.iter().enumerate() {
+		let field = format!("monster_ids[{}]", i);
+		if let Some(monster) = validator.check_monster_id(DATA, &field, monsters, monster_id) {
+			validator.check_single_area(DATA, &field, monster);
+		}
+	}
+}
+
+impl Panigulm {
+	pub fn load(monsters_all: &Monsters) -> Result<Self> {
 		info!("Initialize Panigulm");
 
 		let inner: PanigulmJson = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
-		let mut monsters: Vec<PanigulmMonster<'a>> = Vec::new();
+		let mut monsters: Vec<PanigulmMonster> = Vec::new();
 
 		for monster_id in &inner.monster_ids {
-			match crate::monsters().get(monster_id) {
-				Some(monster) => monsters.push(PanigulmMonster{ monster }),
+			match monsters_all.get(monster_id) {
+				Some(monster) => monsters.push(PanigulmMonster{ monster: Arc::clone(monster) }),
 				None => return Err(
 					Error::UnknownMonsterId(DATA, monster_id.to_owned())
 				)
@@ -50,10 +86,10 @@ impl<'a> Panigulm<'a> {
 		})
 	}
 
-	fn monster_at(&self, at: DateTime<Local>) -> &PanigulmMonster {
+	fn monster_at(&self, at: DateTime<Tz>) -> &PanigulmMonster {
 		let mut ref_date = self.reference_date;
 
-		let monsters: Vec<&PanigulmMonster<'a>> = if at < self.reference_date {
+		let monsters: Vec<&PanigulmMonster> = if at < self.reference_date {
 			ref_date = ref_date - Duration::nanoseconds(1);
 			self.monsters.iter().rev().collect()
 		} else {
@@ -68,8 +104,12 @@ impl<'a> Panigulm<'a> {
 	}
 }
 
-impl<'a> Announcer for Panigulm<'a> {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+impl Announcer for Panigulm {
+	fn name(&self) -> &'static str {
+		"panigulm"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announcement about Panigulm: {:?}", criteria);
 
 		let monster_today = self.monster_at(criteria.at());
@@ -77,33 +117,37 @@ impl<'a> Announcer for Panigulm<'a> {
 		let monster_yesterday = self.monster_at(criteria.at() + Duration::days(-1));
 
 		let announcement = if monster_today != monster_yesterday {
-			self.announcement_at_start
-				.replace("__MONSTER__", monster_today.display())
-				.replace("__RESISTANCES__", monster_today.resistances().display(None::<Vec<String>>).as_str())
+			self.announcement_at_start.announce(&[
+				("__MONSTER__", monster_today.display()),
+				("__RESISTANCES__", monster_today.resistances().display(None::<Vec<String>>).as_str()),
+			])
 		} else if monster_today != monster_tomorrow {
-			self.announcement_at_end
-				.replace("__MONSTER1__", monster_today.display())
-				.replace("__MONSTER2__", monster_tomorrow.display())
+			self.announcement_at_end.announce(&[
+				("__MONSTER1__", monster_today.display()),
+				("__MONSTER2__", monster_tomorrow.display()),
+			])
 		} else {
-			self.announcement
-				.replace("__MONSTER__", monster_today.display())
+			self.announcement.announce(&[
+				("__MONSTER__", monster_today.display()),
+			])
 		};
 
 		Some(announcement)
 	}
 }
 
-impl<'a> Responder for Panigulm<'a> {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+impl Responder for Panigulm {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		debug!("Start building response about Panigulm: {:?}", criteria);
 
 		if self.nickname_regex.is_match(criteria.text()) {
 			info!("Text matches some keywords of Panigulm: {}", criteria.text());
 			let monster = self.monster_at(criteria.at());
-			let response = self.information
-				.replace("__MONSTER__", monster.display())
-				.replace("__RESISTANCES__", monster.resistances().display(None::<Vec<String>>).as_str());
-			Some(response)
+			let response = self.information.render(&[
+				("__MONSTER__", monster.display()),
+				("__RESISTANCES__", monster.resistances().display(None::<Vec<String>>).as_str()),
+			]);
+			Some(Response::from(response))
 		} else {
 			debug!("Text unmatched any keywords of Panigulm: {:?}", criteria);
 			None
@@ -112,35 +156,35 @@ impl<'a> Responder for Panigulm<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct PanigulmMonster<'a> {
-	monster: &'a Monster,
+struct PanigulmMonster {
+	monster: Arc<Monster>,
 }
 
-impl<'a> std::ops::Deref for PanigulmMonster<'a> {
+impl std::ops::Deref for PanigulmMonster {
 	type Target = Monster;
 
 	fn deref(&self) -> &Self::Target {
-		self.monster
+		&self.monster
 	}
 }
 
 use std::cmp;
 
-impl<'a> cmp::PartialEq for PanigulmMonster<'a> {
+impl cmp::PartialEq for PanigulmMonster {
 	fn eq(&self, other: &Self) -> bool {
 		self.id() == other.id()
 	}
 }
 
-impl<'a> cmp::PartialOrd for PanigulmMonster<'a> {
+impl cmp::PartialOrd for PanigulmMonster {
 	fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
 		Some(self.cmp(other))
 	}
 }
 
-impl<'a> cmp::Eq for PanigulmMonster<'a> {}
+impl cmp::Eq for PanigulmMonster {}
 
-impl<'a> cmp::Ord for PanigulmMonster<'a> {
+impl cmp::Ord for PanigulmMonster {
 	fn cmp(&self, other: &Self) -> cmp::Ordering {
 		self.id().cmp(other.id())
 	}
@@ -148,18 +192,19 @@ impl<'a> cmp::Ord for PanigulmMonster<'a> {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PanigulmJson {
-	reference_date: DateTime<Local>,
+	#[serde(deserialize_with = "transform_string_to_datetime")]
+	reference_date: DateTime<Tz>,
 	num_days: i64,
-	announcement: String,
-	announcement_at_start: String,
-	announcement_at_end: String,
-	information: String,
+	announcement: Template,
+	announcement_at_start: Template,
+	announcement_at_end: Template,
+	information: Template,
 	#[serde(deserialize_with = "transform_string_to_regex")]
 	nickname_regex: regex::Regex,
 	monster_ids: Vec<String>,
 }
 
-impl<'a> std::ops::Deref for Panigulm<'a> {
+impl std::ops::Deref for Panigulm {
 	type Target = PanigulmJson;
 
 	fn deref(&self) -> &Self::Target {
@@ -172,59 +217,60 @@ impl<'a> std::ops::Deref for Panigulm<'a> {
 pub(crate) mod tests {
 	use super::*;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_current_positive() {
 		let pani = data();
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 1, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 1, 6, 0, 0).unwrap()).id(),
 			"panigulm_jigenryu"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 4, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 4, 5, 59, 59).unwrap()).id(),
 			"panigulm_jigenryu"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 4, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 4, 6, 0, 0).unwrap()).id(),
 			"panigulm_fordina"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 7, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 7, 5, 59, 59).unwrap()).id(),
 			"panigulm_fordina"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 7, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 7, 6, 0, 0).unwrap()).id(),
 			"panigulm_dydalmos"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 25, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 25, 5, 59, 59).unwrap()).id(),
 			"panigulm_almana"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 25, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 25, 6, 0, 0).unwrap()).id(),
 			"panigulm_jigenryu"
 		);
 
 		// run `cargo test -- --nocapture`
 		let ac = AnnouncementCriteria {
-			at: Local.with_ymd_and_hms(2025, 2, 1, 6, 0, 0).unwrap(),
+			at: Tokyo.with_ymd_and_hms(2025, 2, 1, 6, 0, 0).unwrap(),
 		};
 		println!("Announce: {:#?}", pani.announce(&ac));
 
 		let ac = AnnouncementCriteria {
-			at: Local.with_ymd_and_hms(2022, 2, 4, 6, 0, 0).unwrap(),
+			at: Tokyo.with_ymd_and_hms(2022, 2, 4, 6, 0, 0).unwrap(),
 		};
 		println!("Announce: {:#?}", pani.announce(&ac));
 
 		let ac = AnnouncementCriteria {
-			at: Local.with_ymd_and_hms(2022, 2, 24, 5, 59, 59).unwrap(),
+			at: Tokyo.with_ymd_and_hms(2022, 2, 24, 5, 59, 59).unwrap(),
 		};
 		println!("Announce: {:#?}", pani.announce(&ac));
 	}
@@ -234,37 +280,37 @@ pub(crate) mod tests {
 		let pani = data();
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 2, 1, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 2, 1, 5, 59, 59).unwrap()).id(),
 			"panigulm_almana"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 1, 29, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 1, 29, 6, 0, 0).unwrap()).id(),
 			"panigulm_almana"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 1, 29, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 1, 29, 5, 59, 59).unwrap()).id(),
 			"panigulm_elgios"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 1, 26, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 1, 26, 6, 0, 0).unwrap()).id(),
 			"panigulm_elgios"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 1, 10, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 1, 10, 5, 59, 59).unwrap()).id(),
 			"panigulm_jigenryu"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 1, 8, 6, 0, 0).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 1, 8, 6, 0, 0).unwrap()).id(),
 			"panigulm_jigenryu"
 		);
 
 		assert_eq!(
-			pani.monster_at(Local.with_ymd_and_hms(2025, 1, 8, 5, 59, 59).unwrap()).id(),
+			pani.monster_at(Tokyo.with_ymd_and_hms(2025, 1, 8, 5, 59, 59).unwrap()).id(),
 			"panigulm_almana"
 		);
 	}
@@ -273,26 +319,27 @@ pub(crate) mod tests {
 	fn test_respond() {
 		let pani = data();
 
-		let rc = ResponseCriteria { text: "あああパニパニあああ".to_owned(), at: Local.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
+		let rc = ResponseCriteria { text: "あああパニパニあああ".to_owned(), at: Tokyo.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
 		assert!(pani.respond(&rc).is_some());
 
-		let rc = ResponseCriteria { text: "源世庫".to_owned(), at: Local.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
+		let rc = ResponseCriteria { text: "源世庫".to_owned(), at: Tokyo.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
 		assert!(pani.respond(&rc).is_some());
 
-		let rc = ResponseCriteria { text: "パニゴロモ".to_owned(), at: Local.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
+		let rc = ResponseCriteria { text: "パニゴロモ".to_owned(), at: Tokyo.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
 		assert!(pani.respond(&rc).is_none());
 
-		let rc = ResponseCriteria { text: "源世庫".to_owned(), at: Local.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
+		let rc = ResponseCriteria { text: "源世庫".to_owned(), at: Tokyo.with_ymd_and_hms(2022, 2, 22, 7, 0, 0).unwrap() };
 		println!("Response: {:?}", pani.respond(&rc));
 	}
 
-	pub(crate) fn data<'a>() -> Panigulm<'a> {
+	pub(crate) fn data() -> Panigulm {
 		let inner: PanigulmJson = serde_json::from_str(DATA).unwrap();
-		let mut monsters: Vec<PanigulmMonster<'a>> = Vec::new();
+		let monsters_all = Monsters::load().unwrap();
+		let mut monsters: Vec<PanigulmMonster> = Vec::new();
 
 		for monster_id in &inner.monster_ids {
 			monsters.push(PanigulmMonster {
-				monster: crate::monsters().get(monster_id).unwrap()
+				monster: Arc::clone(monsters_all.get(monster_id).unwrap())
 			});
 		}
 
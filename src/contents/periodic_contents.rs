@@ -1,14 +1,16 @@
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ DateTime, Duration, Local };
+use chrono::{ DateTime, Duration };
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
 };
-use super::{ Announcer, AnnouncementCriteria };
+use super::{ Announcement, Announcer, AnnouncementCriteria, Text };
 
-const DATA: &str = "drakeema-data/contents/periodic_contents.json";
+const DATA: &str = "contents/periodic_contents.json";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PeriodicContents {
@@ -22,21 +24,21 @@ impl PeriodicContents {
 		info!("Initialize PeriodicContents");
 
 		serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))
 	}
 
-	fn contents_at_day(&self, at: &DateTime<Local>) -> String {
+	fn contents_at_day(&self, at: &DateTime<Tz>) -> String {
 		self.contents(at, &self.announcement_at_day)
 	}
 
-	fn contents_at_day_before(&self, at: &DateTime<Local>) -> String {
+	fn contents_at_day_before(&self, at: &DateTime<Tz>) -> String {
 		let tomorrow = *at + Duration::days(1);
 		self.contents(&tomorrow, &self.announcement_at_day_before)
 	}
 
-	fn contents(&self, at: &DateTime<Local>, template: &str) -> String {
+	fn contents(&self, at: &DateTime<Tz>, template: &str) -> String {
 		use chrono::Datelike;
 
 		let contents = self.contents.iter()
@@ -58,7 +60,11 @@ impl PeriodicContents {
 }
 
 impl Announcer for PeriodicContents {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+	fn name(&self) -> &'static str {
+		"periodic_contents"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announcement about PeriodicContents: {:?}", criteria);
 
 		let contents = vec![
@@ -74,7 +80,7 @@ impl Announcer for PeriodicContents {
 			debug!("Nothing announcement about periodic_contents: {:?}", criteria);
 			None
 		} else {
-			Some(contents)
+			Some(Announcement::from(Text::from(contents)))
 		}
 	}
 }
@@ -92,20 +98,21 @@ struct Content {
 pub(crate) mod tests {
 	use super::*;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_contents_at_day_is_exist() {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 10, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 10, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "テンの日です！");
+		assert_eq!(an.unwrap().text(), "テンの日です！");
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 12, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 12, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "じゅうににちで12日です！");
+		assert_eq!(an.unwrap().text(), "じゅうににちで12日です！");
 	}
 
 	#[test]
@@ -113,9 +120,9 @@ pub(crate) mod tests {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 8, 9, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 8, 9, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "明日はテンの日です！");
+		assert_eq!(an.unwrap().text(), "明日はテンの日です！");
 	}
 
 	#[test]
@@ -123,9 +130,9 @@ pub(crate) mod tests {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 2, 9, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 2, 9, 12, 0, 0).unwrap())
 		);
-		assert_eq!(an.unwrap(), "プクの日です！\n明日はテンの日です！");
+		assert_eq!(an.unwrap().text(), "プクの日です！\n明日はテンの日です！");
 	}
 
 	#[test]
@@ -133,7 +140,7 @@ pub(crate) mod tests {
 		let pc = data();
 
 		let an = pc.announce(
-			&AnnouncementCriteria::new(Local.with_ymd_and_hms(2020, 2, 5, 12, 0, 0).unwrap())
+			&AnnouncementCriteria::new(Tokyo.with_ymd_and_hms(2020, 2, 5, 12, 0, 0).unwrap())
 		);
 		assert!(an.is_none());
 	}
@@ -0,0 +1,93 @@
+use crate::{
+	Error,
+	Monsters,
+	Result,
+};
+use super::*;
+
+/// Names of the contents which can announce, in the default order.
+pub const ANNOUNCERS: [&str; 8] = [
+	"periodic_contents",
+	"monthly_contents",
+	"weekly_contents",
+	"seishugosha",
+	"jashin",
+	"panigulm",
+	"konmeiko",
+	"weekly_activity",
+];
+
+/// Names of the contents which can respond, in the default order.
+pub const RESPONDERS: [&str; 7] = [
+	"jashin",
+	"seishugosha",
+	"panigulm",
+	"konmeiko",
+	"boueigun",
+	"weekly_contents",
+	"monsters",
+];
+
+/// Announcers which need the connection to the server.
+const ONLINE_ANNOUNCERS: [&str; 1] = [
+	"weekly_activity",
+];
+
+pub fn is_online(name: &str) -> bool {
+	ONLINE_ANNOUNCERS.contains(&name)
+}
+
+/// Load the content by the name as an announcer.
+pub fn load_announcer(name: &str, monsters: &Monsters) -> Result<Box<dyn Announcer>> {
+	debug!("Load announcer: {}", name);
+
+	Ok(match name {
+		"periodic_contents" => Box::new(PeriodicContents::load()?),
+		"monthly_contents" => Box::new(MonthlyContents::load()?),
+		"weekly_contents" => Box::new(WeeklyContents::load()?),
+		"seishugosha" => Box::new(Seishugosha::load(monsters)?),
+		"jashin" => Box::new(Jashin::load(monsters)?),
+		"panigulm" => Box::new(Panigulm::load(monsters)?),
+		"konmeiko" => Box::new(Konmeiko::load(monsters)?),
+		"weekly_activity" => Box::new(WeeklyActivity::load()?),
+		_ => return Err(Error::UnknownContent(name.to_owned())),
+	})
+}
+
+/// Load the content by the name as a responder.
+pub fn load_responder(name: &str, monsters: &Monsters) -> Result<Box<dyn Responder>> {
+	debug!("Load responder: {}", name);
+
+	Ok(match name {
+		"jashin" => Box::new(Jashin::load(monsters)?),
+		"seishugosha" => Box::new(Seishugosha::load(monsters)?),
+		"panigulm" => Box::new(Panigulm::load(monsters)?),
+		"konmeiko" => Box::new(Konmeiko::load(monsters)?),
+		"boueigun" => Box::new(Boueigun::load(monsters)?),
+		"weekly_contents" => Box::new(WeeklyContents::load()?),
+		"monsters" => Box::new(monsters.clone()),
+		_ => return Err(Error::UnknownContent(name.to_owned())),
+	})
+}
+
+pub fn default_announcers() -> Vec<String> {
+	ANNOUNCERS.iter().map(|n| n.to_string()).collect()
+}
+
+pub fn default_responders() -> Vec<String> {
+	RESPONDERS.iter().map(|n| n.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_unknown_content() {
+		let monsters = Monsters::load().unwrap();
+
+		assert!(matches!(load_announcer("boueigun", &monsters), Err(Error::UnknownContent(_))));
+		assert!(matches!(load_responder("periodic_contents", &monsters), Err(Error::UnknownContent(_))));
+		assert!(load_responder("monsters", &monsters).is_ok());
+	}
+}
@@ -1,39 +1,67 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ DateTime, Local, };
+use std::sync::Arc;
+use chrono::DateTime;
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
-	monsters::Monster,
-	utils::transform_string_to_regex,
+	config,
+	monsters::{ Monster, Monsters },
+	utils::{
+		transform_string_to_datetime,
+		transform_string_to_regex,
+	},
+	validation::Validator,
 };
 use super::{
+	Announcement,
 	Announcer,
 	AnnouncementCriteria,
 	Responder,
+	Response,
 	ResponseCriteria,
+	Text,
 };
 
-const DATA: &str = "drakeema-data/contents/seishugosha.json";
+const DATA: &str = "contents/seishugosha.json";
 
 #[derive(Debug, Clone)]
-pub struct Seishugosha<'a> {
-	monsters: SeishugoshaMonsters<'a>,
+pub struct Seishugosha {
+	monsters: SeishugoshaMonsters,
 	inner: SeishugoshaJson,
 }
 
-impl<'a> Seishugosha<'a> {
-	pub fn load() -> Result<Self> {
+pub fn validate(validator: &mut Validator, monsters: &HashMap<String, Monster>) {
+	let json: SeishugoshaJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	validator.check_placeholders(DATA, "announcement.start", &json.announcement.start, &[]);
+	validator.check_placeholders(DATA, "announcement.parts", &json.announcement.parts, &["__NAME__", "__LEVEL__"]);
+	validator.check_placeholders(DATA, "announcement.end", &json.announcement.end, &[]);
+	validator.check_not_empty(DATA, "level_names", &json.level_names);
+	validator.check_not_empty(DATA, "monsters", &json.monsters);
+
+	for (i, monster) in json.monsters.iter().enumerate() {
+		validator.check_monster_id(DATA, &format!("monsters[{}].monster_id", i), monsters, &monster.monster_id);
+	}
+}
+
+impl Seishugosha {
+	pub fn load(monsters: &Monsters) -> Result<Self> {
 		info!("Initialize Seishugosha");
 
 		let inner: SeishugoshaJson = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
 		Ok(Seishugosha {
-			monsters: SeishugoshaMonsters::new(&inner.monsters)?,
+			monsters: SeishugoshaMonsters::new(&inner.monsters, monsters)?,
 			inner,
 		})
 	}
@@ -42,7 +70,7 @@ impl<'a> Seishugosha<'a> {
 		self.nickname_regex.is_match(text.as_ref())
 	}
 
-	fn level_name(&self, at: DateTime<Local>, offset: i64) -> &str {
+	fn level_name(&self, at: DateTime<Tz>, offset: i64) -> &str {
 		let mut level_index = (self.elapsed_days(at) + offset) % self.level_names.len() as i64;
 		if level_index < 0 {
 			level_index += self.level_names.len() as i64;
@@ -50,7 +78,7 @@ impl<'a> Seishugosha<'a> {
 		self.level_names[level_index as usize].as_str()
 	}
 
-	fn elapsed_days(&self, at: DateTime<Local>) -> i64 {
+	fn elapsed_days(&self, at: DateTime<Tz>) -> i64 {
 		use chrono::Duration;
 
 		if at < self.reference_date {
@@ -61,8 +89,12 @@ impl<'a> Seishugosha<'a> {
 	}
 }
 
-impl<'a> Announcer for Seishugosha<'a> {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+impl Announcer for Seishugosha {
+	fn name(&self) -> &'static str {
+		"seishugosha"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announcement about Seishugosha: {:?}", criteria);
 
 		let parts = self.monsters.iter()
@@ -79,18 +111,18 @@ impl<'a> Announcer for Seishugosha<'a> {
 			&parts +
 			&self.announcement.end;
 		
-		Some(announcement)
+		Some(Announcement::from(Text::from(announcement)))
 	}
 }
 
-impl<'a> Responder for Seishugosha<'a> {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+impl Responder for Seishugosha {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		debug!("Start to reaction about seishugosha: {:?}", criteria);
 
 		if self.is_match(criteria.text()) {
 			let reaction = self.announce(&AnnouncementCriteria::new(criteria.at()));
 			info!("Text matched keywords of Seishugosha: {}", criteria.text());
-			reaction
+			reaction.map(|a| Response::from(a.render()))
 		} else {
 			debug!("Nothing response about seishugosha: {:?}", criteria);
 			None
@@ -98,7 +130,7 @@ impl<'a> Responder for Seishugosha<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for Seishugosha<'a> {
+impl std::ops::Deref for Seishugosha {
 	type Target = SeishugoshaJson;
 
 	fn deref(&self) -> &Self::Target {
@@ -107,20 +139,19 @@ impl<'a> std::ops::Deref for Seishugosha<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct SeishugoshaMonsters<'a> {
-	inner: Vec<SeishugoshaMonster<'a>>,
+struct SeishugoshaMonsters {
+	inner: Vec<SeishugoshaMonster>,
 }
 
-impl<'a> SeishugoshaMonsters<'a> {
-	fn new(s_monsters: impl AsRef<[MonsterJson]>) -> Result<Self> {
-		let mut inner: Vec<SeishugoshaMonster<'a>> = Vec::new();
-		let monsters = crate::monsters();
+impl SeishugoshaMonsters {
+	fn new(s_monsters: impl AsRef<[MonsterJson]>, monsters: &Monsters) -> Result<Self> {
+		let mut inner: Vec<SeishugoshaMonster> = Vec::new();
 
 		for monster in s_monsters.as_ref() {
 			match monsters.get(&monster.monster_id) {
 				Some(m) => inner.push(SeishugoshaMonster {
 					id: monster.id.to_owned(),
-					monster: m,
+					monster: Arc::clone(m),
 					offset: monster.offset,
 				}),
 				None => return Err(
@@ -135,8 +166,8 @@ impl<'a> SeishugoshaMonsters<'a> {
 	}
 }
 
-impl<'a> std::ops::Deref for SeishugoshaMonsters<'a> {
-	type Target = Vec<SeishugoshaMonster<'a>>;
+impl std::ops::Deref for SeishugoshaMonsters {
+	type Target = Vec<SeishugoshaMonster>;
 
 	fn deref(&self) -> &Self::Target {
 		&self.inner
@@ -144,15 +175,15 @@ impl<'a> std::ops::Deref for SeishugoshaMonsters<'a> {
 }
 
 #[derive(Debug, Clone)]
-struct SeishugoshaMonster<'a> {
+struct SeishugoshaMonster {
 	#[allow(unused)]
 	id: String,
-	monster: &'a Monster,
+	monster: Arc<Monster>,
 	offset: i64,
 }
 
-impl<'a> std::ops::Deref for SeishugoshaMonster<'a> {
-	type Target = &'a Monster;
+impl std::ops::Deref for SeishugoshaMonster {
+	type Target = Monster;
 
 	fn deref(&self) -> &Self::Target {
 		&self.monster
@@ -161,7 +192,8 @@ impl<'a> std::ops::Deref for SeishugoshaMonster<'a> {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SeishugoshaJson {
-	reference_date: DateTime<Local>,
+	#[serde(deserialize_with = "transform_string_to_datetime")]
+	reference_date: DateTime<Tz>,
 	level_names: Vec<String>,
 	announcement: AnnouncerJson,
 	#[allow(dead_code)]
@@ -189,34 +221,35 @@ struct MonsterJson {
 pub(crate) mod tests {
 	use super::*;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_positive() {
 		let ssgs = data();
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 20, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 20, 6, 0, 0).unwrap(), 0),
 			"Ⅰ"
 		);
 
 		// Edge of first day
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 21, 5, 59, 59).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 21, 5, 59, 59).unwrap(), 0),
 			"Ⅰ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 21, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 21, 6, 0, 0).unwrap(), 0),
 			"Ⅱ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 22, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 22, 6, 0, 0).unwrap(), 0),
 			"Ⅲ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 23, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 23, 6, 0, 0).unwrap(), 0),
 			"Ⅰ"
 		);
 	}
@@ -226,42 +259,42 @@ pub(crate) mod tests {
 		let ssgs = data();
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 20, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 20, 6, 0, 0).unwrap(), 0),
 			"Ⅰ"
 		);
 
 		// Edge of 1 day ago
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 20, 5, 59, 59).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 20, 5, 59, 59).unwrap(), 0),
 			"Ⅲ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 19, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 19, 6, 0, 0).unwrap(), 0),
 			"Ⅲ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 18, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 18, 6, 0, 0).unwrap(), 0),
 			"Ⅱ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 17, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 17, 6, 0, 0).unwrap(), 0),
 			"Ⅰ"
 		);
 
 		assert_eq!(
-			ssgs.level_name(chrono::Local.with_ymd_and_hms(2018, 4, 16, 6, 0, 0).unwrap(), 0),
+			ssgs.level_name(Tokyo.with_ymd_and_hms(2018, 4, 16, 6, 0, 0).unwrap(), 0),
 			"Ⅲ"
 		);
 
 	}
 
-	pub(crate) fn data<'a>() -> Seishugosha<'a> {
+	pub(crate) fn data() -> Seishugosha {
 		let inner: SeishugoshaJson = serde_json::from_str(TEST_DATA).unwrap();
 		Seishugosha {
-			monsters: SeishugoshaMonsters::new(&inner.monsters).unwrap(),
+			monsters: SeishugoshaMonsters::new(&inner.monsters, &Monsters::load().unwrap()).unwrap(),
 			inner,
 		}
 	}
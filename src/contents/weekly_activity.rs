@@ -1,22 +1,24 @@
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ Duration, Local, DateTime };
+use chrono::{ Duration, DateTime };
 use mastors::prelude::*;
 use mastors::api::v1::instance::activity;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
+	timezone,
 	tmp_file,
 };
-use super::{ Announcer, AnnouncementCriteria };
+use super::{ Announcement, Announcer, AnnouncementCriteria, Template };
 
-const DATA: &str = "drakeema-data/contents/weekly_activity.json";
+const DATA: &str = "contents/weekly_activity.json";
 const TMP: &str = "weekly_activity.tmp";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct WeeklyActivity {
-	announcement: String,
+	announcement: Template,
 }
 
 impl WeeklyActivity {
@@ -24,14 +26,18 @@ impl WeeklyActivity {
 		info!("Initialize WeeklyActivity");
 
 		serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))
 	}
 }
 
 impl Announcer for WeeklyActivity {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+	fn name(&self) -> &'static str {
+		"weekly_activity"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announcement about WeeklyActivities: {:?}", criteria);
 
 		use chrono::offset::TimeZone;
@@ -72,7 +78,7 @@ impl Announcer for WeeklyActivity {
 			return None;
 		}
 
-		let start_date = Local.from_utc_datetime(
+		let start_date = timezone::timezone().from_utc_datetime(
 			&DateTime::from_timestamp(latest_activity.week(), 0).unwrap().naive_utc()
 		).date_naive();
 		let end_date = start_date + Duration::days(6);
@@ -86,11 +92,12 @@ impl Announcer for WeeklyActivity {
 			return None;
 		}
 
-		let announcement = self.announcement
-			.replace("__START_DATE__", &start_date.format("%Y-%m-%d").to_string())
-			.replace("__END_DATE__", &end_date.format("%Y-%m-%d").to_string())
-			.replace("__ACTIVE_USER__", &latest_activity.logins().to_string())
-			.replace("__STATUS_COUNT__", &latest_activity.statuses().to_string());
+		let announcement = self.announcement.announce(&[
+			("__START_DATE__", &start_date.format("%Y-%m-%d").to_string()),
+			("__END_DATE__", &end_date.format("%Y-%m-%d").to_string()),
+			("__ACTIVE_USER__", &latest_activity.logins().to_string()),
+			("__STATUS_COUNT__", &latest_activity.statuses().to_string()),
+		]);
 		
 		Some(announcement)
 	}
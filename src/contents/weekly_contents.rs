@@ -1,46 +1,64 @@
 use std::fs::File;
 use std::io::BufReader;
-use chrono::{ DateTime, Duration, Local };
+use chrono::{ Datelike, DateTime, Duration };
+use chrono_tz::Tz;
+use regex::Regex;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
+	emojis::EmojiConfig,
+	utils::transform_string_to_regex,
+	validation::Validator,
+};
+use super::{
+	Announcement,
+	Announcer,
+	AnnouncementCriteria,
+	Responder,
+	Response,
+	ResponseCriteria,
+	Template,
+	Text,
 };
-use super::{ Announcer, AnnouncementCriteria };
 
-const DATA: &str = "drakeema-data/contents/weekly_contents.json";
+const DATA: &str = "contents/weekly_contents.json";
+const DAYS_OF_WEEK: u32 = 7;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct WeeklyContents {
-	announcement_at_start: String,
-	announcement_at_end: String,
-	contents: Vec<WeeklyContent>,
+	emoji_placeholder: String,
+	inner: WeeklyContentsJson,
 }
 
-use chrono::Datelike;
-
 impl WeeklyContents {
 	pub fn load() -> Result<Self> {
 		info!("Initialize WeeklyContents");
 
-		serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+		let inner: WeeklyContentsJson = serde_json::from_reader(
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
-		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))
+		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
+
+		Ok(WeeklyContents {
+			emoji_placeholder: EmojiConfig::load()?.placeholder().to_owned(),
+			inner,
+		})
 	}
 
-	fn contents_to_start(&self, at: DateTime<Local>) -> String {
+	fn contents_to_start(&self, at: DateTime<Tz>) -> String {
 		self.contents(&at.weekday().num_days_from_sunday(), &self.announcement_at_start)
 	}
 
-	fn contents_to_end(&self, at: DateTime<Local>) -> String {
+	fn contents_to_end(&self, at: DateTime<Tz>) -> String {
 		self.contents(&(at + Duration::days(1)).weekday().num_days_from_sunday(), &self.announcement_at_end)
 	}
 
 	fn contents(&self, wday_num: &u32, template: &str) -> String {
-		let contents = self.iter()
+		let contents = self.contents.iter()
 			.filter(|wc| wc.reset_days.contains(wday_num))
-			.map(|wc| String::from("__EMOJI__ ") + &wc.display)
+			.map(|wc| self.line(&wc.display))
 			.collect::<Vec<String>>()
 			.join("\n");
 
@@ -50,10 +68,63 @@ impl WeeklyContents {
 			template.replace("__CONTENTS__", &contents)
 		}
 	}
+
+	/// Build the lines of all contents, which tell the contents reset today and the days left to reset.
+	fn days_to_reset(&self, at: DateTime<Tz>) -> String {
+		let today = at.weekday().num_days_from_sunday();
+
+		let mut contents = self.contents.iter()
+			.filter_map(|wc| wc.days_to_reset(today).map(|days| (days, wc)))
+			.collect::<Vec<(u32, &WeeklyContent)>>();
+		contents.sort_by_key(|(days, _)| *days);
+
+		contents.iter()
+			.map(|(days, wc)| {
+				let part = if *days == 0 {
+					self.information_parts.today.replace("__NAME__", &wc.display)
+				} else {
+					self.information_parts.days_left
+						.replace("__NAME__", &wc.display)
+						.replace("__DAYS__", &days.to_string())
+				};
+				self.line(&part)
+			})
+			.collect::<Vec<String>>()
+			.join("\n")
+	}
+
+	/// Put the emoji placeholder before the text, which is replaced with a custom emoji when posted.
+	fn line(&self, text: &str) -> String {
+		format!("{} {}", self.emoji_placeholder, text)
+	}
+}
+
+pub fn validate(validator: &mut Validator) {
+	let json: WeeklyContentsJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	validator.check_not_empty(DATA, "contents", &json.contents);
+	validator.check_placeholders(DATA, "announcement_at_start", &json.announcement_at_start, &["__CONTENTS__"]);
+	validator.check_placeholders(DATA, "announcement_at_end", &json.announcement_at_end, &["__CONTENTS__"]);
+	validator.check_template(DATA, "information", &json.information, &["__CONTENTS__"]);
+	validator.check_placeholders(DATA, "information_parts.today", &json.information_parts.today, &["__NAME__"]);
+	validator.check_placeholders(DATA, "information_parts.days_left", &json.information_parts.days_left, &["__NAME__", "__DAYS__"]);
+
+	for content in json.contents.iter() {
+		if let Some(day) = content.reset_days.iter().find(|d| **d >= DAYS_OF_WEEK) {
+			validator.push(DATA, "contents.reset_days", format!("day must be less than 7: {}: {}", content.id, day));
+		}
+	}
 }
 
 impl Announcer for WeeklyContents {
-	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<String> {
+	fn name(&self) -> &'static str {
+		"weekly_contents"
+	}
+
+	fn announce(&self, criteria: &AnnouncementCriteria) -> Option<Announcement> {
 		debug!("Start building announcement about WeeklyContents: {:?}", criteria);
 
 		let announcement = vec![
@@ -69,19 +140,54 @@ impl Announcer for WeeklyContents {
 			debug!("Nothing announcement about WeeklyContents: {:?}", criteria);
 			None
 		} else {
-			Some(announcement)
+			Some(Announcement::from(Text::from(announcement)))
+		}
+	}
+}
+
+impl Responder for WeeklyContents {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
+		debug!("Start building response about WeeklyContents: {:?}", criteria);
+
+		if self.nickname_regex.is_match(criteria.text()) {
+			info!("Text matched keywords of WeeklyContents: {}", criteria.text());
+
+			let response = self.information.render(&[
+				("__CONTENTS__", self.days_to_reset(criteria.at()).as_str()),
+			]);
+			Some(Response::from(response))
+		} else {
+			debug!("Nothing response about WeeklyContents: {:?}", criteria);
+			None
 		}
 	}
 }
 
 impl std::ops::Deref for WeeklyContents {
-	type Target = Vec<WeeklyContent>;
+	type Target = WeeklyContentsJson;
 
 	fn deref(&self) -> &Self::Target {
-		&self.contents
+		&self.inner
 	}
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeeklyContentsJson {
+	announcement_at_start: String,
+	announcement_at_end: String,
+	#[serde(deserialize_with = "transform_string_to_regex")]
+	nickname_regex: Regex,
+	information: Template,
+	information_parts: InformationParts,
+	contents: Vec<WeeklyContent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct InformationParts {
+	today: String,
+	days_left: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct WeeklyContent {
 	#[allow(dead_code)]
@@ -90,18 +196,27 @@ pub struct WeeklyContent {
 	reset_days: Vec<u32>,
 }
 
+impl WeeklyContent {
+	/// Get the days from today to the next reset, which is 0 when the content resets today.
+	fn days_to_reset(&self, today: u32) -> Option<u32> {
+		self.reset_days.iter()
+			.map(|day| (day + DAYS_OF_WEEK - today % DAYS_OF_WEEK) % DAYS_OF_WEEK)
+			.min()
+	}
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use chrono::Local;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_contents_to_start_is_exist() {
 		let wc = data();
-		let sunday = Local.with_ymd_and_hms(2020, 8, 23, 12, 0, 0).unwrap();
-		let monday = Local.with_ymd_and_hms(2020, 8, 24, 12, 0, 0).unwrap();
-		let tuesday = Local.with_ymd_and_hms(2020, 8, 25, 12, 0, 0).unwrap();
+		let sunday = Tokyo.with_ymd_and_hms(2020, 8, 23, 12, 0, 0).unwrap();
+		let monday = Tokyo.with_ymd_and_hms(2020, 8, 24, 12, 0, 0).unwrap();
+		let tuesday = Tokyo.with_ymd_and_hms(2020, 8, 25, 12, 0, 0).unwrap();
 
 		assert!(!(wc.contents_to_start(sunday).is_empty()));
 		assert!(!(wc.contents_to_start(monday).is_empty()));
@@ -111,9 +226,9 @@ pub(crate) mod tests {
 	#[test]
 	fn test_contents_to_start_is_not_exist() {
 		let wc = data();
-		let wednesday = Local.with_ymd_and_hms(2020, 8, 26, 12, 0, 0).unwrap();
-		let thursday = Local.with_ymd_and_hms(2020, 8, 27, 12, 0, 0).unwrap();
-		let friday = Local.with_ymd_and_hms(2020, 8, 28, 12, 0, 0).unwrap();
+		let wednesday = Tokyo.with_ymd_and_hms(2020, 8, 26, 12, 0, 0).unwrap();
+		let thursday = Tokyo.with_ymd_and_hms(2020, 8, 27, 12, 0, 0).unwrap();
+		let friday = Tokyo.with_ymd_and_hms(2020, 8, 28, 12, 0, 0).unwrap();
 
 		assert!(wc.contents_to_start(wednesday).is_empty());
 		assert!(wc.contents_to_start(thursday).is_empty());
@@ -123,9 +238,9 @@ pub(crate) mod tests {
 	#[test]
 	fn test_contents_to_end_is_exist() {
 		let wc = data();
-		let saturday = Local.with_ymd_and_hms(2020, 8, 22, 12, 0, 0).unwrap();
-		let sunday = Local.with_ymd_and_hms(2020, 8, 23, 12, 0, 0).unwrap();
-		let monday = Local.with_ymd_and_hms(2020, 8, 24, 12, 0, 0).unwrap();
+		let saturday = Tokyo.with_ymd_and_hms(2020, 8, 22, 12, 0, 0).unwrap();
+		let sunday = Tokyo.with_ymd_and_hms(2020, 8, 23, 12, 0, 0).unwrap();
+		let monday = Tokyo.with_ymd_and_hms(2020, 8, 24, 12, 0, 0).unwrap();
 
 		assert!(!(wc.contents_to_end(saturday).is_empty()));
 		assert!(!(wc.contents_to_end(sunday).is_empty()));
@@ -135,23 +250,72 @@ pub(crate) mod tests {
 	#[test]
 	fn test_contents_to_end_is_not_exist() {
 		let wc = data();
-		let tuesday = Local.with_ymd_and_hms(2020, 8, 25, 12, 0, 0).unwrap();
-		let wednesday = Local.with_ymd_and_hms(2020, 8, 26, 12, 0, 0).unwrap();
-		let thursday = Local.with_ymd_and_hms(2020, 8, 27, 12, 0, 0).unwrap();
+		let tuesday = Tokyo.with_ymd_and_hms(2020, 8, 25, 12, 0, 0).unwrap();
+		let wednesday = Tokyo.with_ymd_and_hms(2020, 8, 26, 12, 0, 0).unwrap();
+		let thursday = Tokyo.with_ymd_and_hms(2020, 8, 27, 12, 0, 0).unwrap();
 
 		assert!(wc.contents_to_end(tuesday).is_empty());
 		assert!(wc.contents_to_end(wednesday).is_empty());
 		assert!(wc.contents_to_end(thursday).is_empty());
 	}
 
+	#[test]
+	fn test_respond() {
+		let wc = data();
+		let sunday = Tokyo.with_ymd_and_hms(2020, 8, 23, 12, 0, 0).unwrap();
+
+		assert_eq!(
+			wc.respond(&ResponseCriteria::new(sunday, "キーマさん 今週のリセット教えて")).unwrap().text(),
+			"今週のリセットは……\n\
+			__EMOJI__ 万魔の塔 は今日リセット！\n\
+			__EMOJI__ レンダーシア討伐隊 は今日リセット！\n\
+			__EMOJI__ 達人クエスト は今日リセット！\n\
+			__EMOJI__ モンスタータロット販売 は今日リセット！\n\
+			__EMOJI__ ピラミッドの秘宝 はあと1日でリセット！\n\
+			__EMOJI__ 試練の門 はあと1日でリセット！\n\
+			__EMOJI__ 王家の迷宮 はあと2日でリセット！",
+		);
+		assert!(wc.respond(&ResponseCriteria::new(sunday, "キーマさん 邪神教えて")).is_none());
+	}
+
+	#[test]
+	fn test_days_to_reset() {
+		let wc = data();
+		let shiren = wc.contents.iter().find(|c| c.id == "shiren").unwrap();
+
+		assert_eq!(shiren.days_to_reset(0), Some(1));
+		assert_eq!(shiren.days_to_reset(1), Some(0));
+		assert_eq!(shiren.days_to_reset(2), Some(6));
+	}
+
+	#[test]
+	fn test_announce_with_emoji_placeholder() {
+		let wc = WeeklyContents {
+			emoji_placeholder: "__E__".to_owned(),
+			..data()
+		};
+		let monday = Tokyo.with_ymd_and_hms(2020, 8, 24, 12, 0, 0).unwrap();
+
+		assert!(wc.announce(&AnnouncementCriteria::new(monday)).unwrap().text().contains("__E__ 試練の門"));
+	}
+
 	pub(crate) fn data() -> WeeklyContents {
-		serde_json::from_str(DATA).unwrap()
+		WeeklyContents {
+			emoji_placeholder: "__EMOJI__".to_owned(),
+			inner: serde_json::from_str(DATA).unwrap(),
+		}
 	}
 
 	const DATA: &str = r#"
         {
 			"announcement_at_start": "今週の……\n__CONTENTS__\n……は、今日からです！",
 			"announcement_at_end": "今週の……\n__CONTENTS__\n……は、今日までです！",
+			"nickname_regex": "(?:リセット|週替わり)",
+			"information": "今週のリセットは……\n__CONTENTS__",
+			"information_parts": {
+				"today": "__NAME__ は今日リセット！",
+				"days_left": "__NAME__ はあと__DAYS__日でリセット！"
+			},
 			"contents": [
             	{
             		"id": "banma",
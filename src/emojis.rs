@@ -7,14 +7,18 @@ use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
+	tmp_file,
 	utils::transform_string_to_regex,
+	validation::Validator,
 };
 
-const DATA: &str = "drakeema-data/emojis.json";
+const DATA: &str = "emojis.json";
+const CACHE: &str = "emojis.cache";
 
 #[derive(Debug, Clone)]
 pub struct Emojis<'a> {
-	conn: &'a Connection,
+	conn: Option<&'a Connection>,
 	placeholder: String,
 	re: regex::Regex,
 	rand: rand::rngs::ThreadRng,
@@ -25,24 +29,48 @@ pub struct Emojis<'a> {
 impl<'a> Emojis<'a> {
 	pub fn load(conn: &'a Connection) -> Result<Self> {
 		info!("Initialize Emojis");
-		let config: EmojiConfig = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
-		)
-		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
+		let config = EmojiConfig::load()?;
 
-		custom_emojis::get(conn)
+		let emojis = custom_emojis::get(conn)
 			.send()
-			.map(|ce| {
-				Emojis {
-					conn,
-					placeholder: config.placeholder.clone(),
-					re: config.category_regex.clone(),
-					rand: rand::thread_rng(),
-					inner: Self::build_emojis(&config.category_regex, &ce),
-					cache: Self::build_emojis(&config.category_regex, &ce),
-				}
-			})
-			.map_err(Error::MastorsApi)
+			.map(|ce| Self::build_emojis(&config.category_regex, &ce))
+			.map_err(Error::MastorsApi)?;
+
+		if let Err(e) = Self::save_cache(&emojis) {
+			warn!("Failed to save the cache of emojis: {}", e);
+		}
+
+		Ok(Emojis {
+			conn: Some(conn),
+			placeholder: config.placeholder,
+			re: config.category_regex,
+			rand: rand::thread_rng(),
+			inner: emojis.clone(),
+			cache: emojis,
+		})
+	}
+
+	/// Load emojis from the cache saved at the last time connected to the server.
+	pub fn load_cached() -> Result<Self> {
+		info!("Initialize Emojis from the cache");
+		let config = EmojiConfig::load()?;
+
+		let emojis: Vec<String> = match tmp_file::load_tmp_as_string(CACHE)? {
+			Some(json) => serde_json::from_str(&json)
+				.map_err(|e| Error::UnparseableJson(CACHE.to_owned(), e))?,
+			None => return Err(
+				Error::DataNotPresented(CACHE, "cache of emojis".to_owned())
+			),
+		};
+
+		Ok(Emojis {
+			conn: None,
+			placeholder: config.placeholder,
+			re: config.category_regex,
+			rand: rand::thread_rng(),
+			inner: emojis.clone(),
+			cache: emojis,
+		})
 	}
 
 	pub fn emoji(&mut self) -> String {
@@ -73,10 +101,21 @@ impl<'a> Emojis<'a> {
 	fn refresh(&mut self) {
 		info!("Start refresh Emojis: inner.len: {}, cache.len: {}", self.len(), self.cache.len());
 
-		match custom_emojis::get(self.conn).send() {
+		let conn = match self.conn {
+			Some(conn) => conn,
+			None => {
+				self.inner = self.cache.clone();
+				return;
+			},
+		};
+
+		match custom_emojis::get(conn).send() {
 			Ok(ce) => {
 				self.inner = Self::build_emojis(&self.re, &ce);
 				self.cache = Self::build_emojis(&self.re, &ce);
+				if let Err(e) = Self::save_cache(&self.cache) {
+					warn!("Failed to save the cache of emojis: {}", e);
+				}
 				info!("Emojis refresh completed: remains: {}", self.inner.len())
 			},
 			Err(e) => {
@@ -98,6 +137,12 @@ impl<'a> Emojis<'a> {
 
 		emojis
 	}
+
+	fn save_cache(emojis: &[String]) -> Result<()> {
+		let json = serde_json::to_string(emojis)
+			.map_err(|e| Error::UnserializableJson(CACHE.to_owned(), e))?;
+		tmp_file::save_tmp(CACHE, json)
+	}
 }
 
 impl<'a> std::ops::Deref for Emojis<'a> {
@@ -115,6 +160,27 @@ pub struct EmojiConfig {
 	category_regex: regex::Regex,
 }
 
+impl EmojiConfig {
+	pub fn load() -> Result<Self> {
+		serde_json::from_reader(
+			BufReader::new(File::open(config::data_path(DATA))?)
+		)
+		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))
+	}
+
+	pub fn validate(validator: &mut Validator) -> Option<Self> {
+		let config: Self = validator.parse(DATA)?;
+		if config.placeholder.is_empty() {
+			validator.push(DATA, "placeholder", "placeholder must not be empty");
+		}
+		Some(config)
+	}
+
+	pub fn placeholder(&self) -> &str {
+		&self.placeholder
+	}
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
@@ -149,7 +215,21 @@ pub(crate) mod tests {
 		let config: EmojiConfig = serde_json::from_str(CONFIG).unwrap();
 		let ce: mastors::entities::Emojis = serde_json::from_str(DATA).unwrap();
 		super::Emojis {
-			conn,
+			conn: Some(conn),
+			placeholder: config.placeholder.clone(),
+			re: config.category_regex.clone(),
+			rand: rand::thread_rng(),
+			inner: super::Emojis::build_emojis(&config.category_regex, &ce),
+			cache: super::Emojis::build_emojis(&config.category_regex, &ce),
+		}
+	}
+
+	/// Emojis which are given from the cache without the connection to the server.
+	pub(crate) fn cached() -> super::Emojis<'static> {
+		let config: EmojiConfig = serde_json::from_str(CONFIG).unwrap();
+		let ce: mastors::entities::Emojis = serde_json::from_str(DATA).unwrap();
+		super::Emojis {
+			conn: None,
 			placeholder: config.placeholder.clone(),
 			re: config.category_regex.clone(),
 			rand: rand::thread_rng(),
@@ -11,6 +11,12 @@ pub enum Error {
 		serde_json::Error,
 	),
 
+	#[error(display = "serialize json error: {}, {}", _0, _1)]
+	UnserializableJson(
+		String,
+		serde_json::Error,
+	),
+
 	#[error(display = "IO error: {}", _0)]
 	Io(
 		#[error(souce, from)]
@@ -65,11 +71,6 @@ pub enum Error {
 		std::io::Error,
 	),
 
-	#[error(display = "Rate limit exceeded: limit: {}", _0)]
-	ExceedRateLimit(
-		usize
-	),
-
 	#[error(display = "HTTP request error: {}", _0)]
 	HttpRequest(
 		#[error(source, from)]
@@ -81,4 +82,31 @@ pub enum Error {
 		String,
 		feed_rs::parser::ParseFeedError,
 	),
+
+	#[error(display = "Invalid timezone: {}, {}", _0, _1)]
+	InvalidTimezone(
+		String,
+		String,
+	),
+
+	#[error(display = "Invalid configuration: {}, {}", _0, _1)]
+	InvalidConfig(
+		String,
+		String,
+	),
+
+	#[error(display = "Unknown content: {}", _0)]
+	UnknownContent(
+		String,
+	),
+
+	#[error(display = "Channel is closed: {}", _0)]
+	ClosedChannel(
+		&'static str,
+	),
+
+	#[error(display = "Problems found in data files: {}", _0)]
+	InvalidDataFiles(
+		usize,
+	),
 }
@@ -0,0 +1,99 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde_json::{ json, Value };
+
+const FAKE_ID: &str = "1";
+
+/// Build a notification of the mention in the same form as Mastodon, to be processed without the server.
+pub fn notification(text: &str, acct: &str, visibility: &str, at: DateTime<Tz>) -> Value {
+	json!({
+		"id": FAKE_ID,
+		"type": "mention",
+		"created_at": at.to_rfc3339(),
+		"account": account(acct, at),
+		"status": status(text, acct, visibility, at),
+	})
+}
+
+/// Build a status in the same form as Mastodon, to be processed without the server.
+pub fn status(text: &str, acct: &str, visibility: &str, at: DateTime<Tz>) -> Value {
+	json!({
+		"id": FAKE_ID,
+		"uri": "https://localhost/statuses/1",
+		"url": "https://localhost/statuses/1",
+		"created_at": at.to_rfc3339(),
+		"account": account(acct, at),
+		"content": to_html(text),
+		"text": null,
+		"visibility": visibility,
+		"sensitive": false,
+		"spoiler_text": "",
+		"media_attachments": [],
+		"application": null,
+		"mentions": [],
+		"tags": [],
+		"emojis": [],
+		"reblogs_count": 0,
+		"favourites_count": 0,
+		"replies_count": 0,
+		"in_reply_to_id": null,
+		"in_reply_to_account_id": null,
+		"reblog": null,
+		"poll": null,
+		"card": null,
+		"language": null,
+	})
+}
+
+fn account(acct: &str, at: DateTime<Tz>) -> Value {
+	let username = acct.split('@').next().unwrap_or(acct);
+
+	json!({
+		"id": FAKE_ID,
+		"username": username,
+		"acct": acct,
+		"display_name": username,
+		"locked": false,
+		"bot": false,
+		"discoverable": null,
+		"group": false,
+		"created_at": at.to_rfc3339(),
+		"note": "",
+		"url": "https://localhost/@".to_owned() + username,
+		"avatar": "https://localhost/avatar.png",
+		"avatar_static": "https://localhost/avatar.png",
+		"header": "https://localhost/header.png",
+		"header_static": "https://localhost/header.png",
+		"followers_count": 0,
+		"following_count": 0,
+		"statuses_count": 0,
+		"last_status_at": null,
+		"emojis": [],
+		"fields": [],
+	})
+}
+
+/// Build the content of the status in the same form as Mastodon.
+fn to_html(text: &str) -> String {
+	let escaped = text
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;");
+
+	escaped.split("\n\n")
+		.map(|p| format!("<p>{}</p>", p.replace('\n', "<br />")))
+		.collect::<Vec<String>>()
+		.join("")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_to_html() {
+		assert_eq!(to_html("キーマさん"), "<p>キーマさん</p>");
+		assert_eq!(to_html("キーマさん\n<邪神>\n\nおしえて"), "<p>キーマさん<br />&lt;邪神&gt;</p><p>おしえて</p>");
+	}
+}
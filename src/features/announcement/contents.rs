@@ -1,83 +1,177 @@
 use std::fs::File;
 use std::io::BufReader;
-use std::sync::Arc;
-use std::sync::mpsc;
+use std::panic::{ self, AssertUnwindSafe };
+use std::sync::{ Arc, mpsc };
 use std::thread;
 use std::time::Duration as StdDuration;
-use chrono::{ DateTime, Duration, Local, NaiveTime };
-use mastors::entities::Visibility;
+use chrono::{ DateTime, Duration, NaiveDate, NaiveTime, TimeZone };
+use chrono_tz::Tz;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Message,
+	Monsters,
 	Result,
+	config,
+	clock::Clock,
 	contents::*,
+	logging::LogContext,
+	reload::{ DataSet, Swappable },
+	signals,
+	supervisor::Worker,
+	systemd,
+	timezone,
+	validation::Validator,
 };
 
-const DATA: &str = "drakeema-data/features/announcement/contents.json";
+const DATA: &str = "features/announcement/contents.json";
 
 pub struct ContentsWorker {
-	contents: Arc<Vec<Box<dyn Announcer>>>,
-	announcement_times: Arc<AnnouncementTimes>,
+	clock: Arc<dyn Clock>,
+	data: Arc<Swappable<DataSet>>,
 }
 
 impl ContentsWorker {
-	pub fn load()-> Result<Self> {
+	pub fn new(clock: Arc<dyn Clock>, data: Arc<Swappable<DataSet>>) -> Self {
 		info!("Initialize ContentsWorker");
 
+		ContentsWorker {
+			clock,
+			data,
+		}
+	}
+
+	fn announce_now(&self) -> Vec<Announcement> {
+		self.data.load().contents().announce(self.clock.now())
+	}
+}
+
+/// Contents to announce and the times to announce them.
+pub struct Contents {
+	contents: Vec<Box<dyn Announcer>>,
+	announcement_times: AnnouncementTimes,
+}
+
+impl Contents {
+	pub fn load(monsters: &Monsters) -> Result<Self> {
+		info!("Initialize Contents");
+		Self::load_with(monsters, true)
+	}
+
+	/// Load the contents which don't need the connection to the server.
+	pub fn load_offline(monsters: &Monsters) -> Result<Self> {
+		Self::load_with(monsters, false)
+	}
+
+	/// Load the announcers given in the data file in the order.
+	fn load_with(monsters: &Monsters, online: bool) -> Result<Self> {
 		let json: Json = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
-		let contents: Vec<Box<dyn Announcer>> = vec![
-			Box::new(PeriodicContents::load()?),
-			Box::new(MonthlyContents::load()?),
-			Box::new(Seishugosha::load()?),
-			Box::new(Jashin::load()?),
-			Box::new(Panigulm::load()?),
-			Box::new(Konmeiko::load()?),
-			Box::new(WeeklyActivity::load()?),
-		];
-
-		Ok(ContentsWorker {
-			contents: Arc::new(contents),
-			announcement_times: Arc::new(AnnouncementTimes::new(json.announcement_times)),
+		let mut contents: Vec<Box<dyn Announcer>> = Vec::new();
+		for name in json.announcers.iter() {
+			if !online && registry::is_online(name) {
+				info!("Skip the announcer which needs the connection: {}", name);
+				continue;
+			}
+			contents.push(registry::load_announcer(name, monsters)?);
+		}
+		info!("Announcers: {:?}", json.announcers);
+
+		Ok(Contents {
+			contents,
+			announcement_times: AnnouncementTimes::new(json.announcement_times),
 		})
 	}
 
-	pub fn start(&self, tx: mpsc::Sender<Message>) {
-		let contents = Arc::clone(&self.contents);
-		let announcement_times = Arc::clone(&self.announcement_times);
+	/// Build announcements at every announcement time between the dates, both inclusive.
+	pub fn simulate(&self, from: NaiveDate, to: NaiveDate) -> Vec<(DateTime<Tz>, Vec<Announcement>)> {
+		let mut announcements = Vec::new();
+		let mut date = from;
 
-		thread::spawn(move || {loop {
-			let duration_secs = announcement_times.duration_secs(Local::now());
+		while date <= to {
+			for time in self.announcement_times.iter() {
+				let at = match timezone::timezone().from_local_datetime(&date.and_time(*time)).earliest() {
+					Some(at) => at,
+					None => continue,
+				};
+				announcements.push((at, self.announce(at)));
+			}
+			date = match date.succ_opt() {
+				Some(date) => date,
+				None => break,
+			};
+		}
+
+		announcements
+	}
+
+	/// Build announcements about all contents, a content which fails doesn't stop the others.
+	fn announce(&self, at: DateTime<Tz>) -> Vec<Announcement> {
+		let criteria = AnnouncementCriteria::new(at);
+
+		info!("Start announcing about contents: {:?}", criteria);
+		let announcements = self.contents.iter()
+			.filter_map(|c| match panic::catch_unwind(AssertUnwindSafe(|| c.announce(&criteria))) {
+				Ok(announcement) => announcement,
+				Err(_) => {
+					error!("Failed to announce about content, skip it: {}", c.name());
+					None
+				},
+			})
+			.collect::<Vec<Announcement>>();
+
+		Announcement::combine(announcements, "\n\n")
+	}
+}
+
+impl Worker for ContentsWorker {
+	fn name(&self) -> &'static str {
+		"contents"
+	}
+
+	fn run(&self, tx: mpsc::Sender<Message>) {
+		loop {
+			let version = self.data.version();
+			let duration_secs = self.data.load().contents().announcement_times.duration_secs(self.clock.now());
 
 			info!("Next announcement about contents will be in {} secs", duration_secs);
-			thread::sleep(StdDuration::from_secs(duration_secs));
+			systemd::status(format!(
+				"Next announcement about contents at {}",
+				(self.clock.now() + Duration::seconds(duration_secs as i64)).format("%Y-%m-%d %H:%M"),
+			));
 
-			let criteria = AnnouncementCriteria::new(Local::now());
+			// The announcement times may be changed by reloading while waiting.
+			if self.data.wait_for_change(version, StdDuration::from_secs(duration_secs)) {
+				info!("Data files are reloaded, recalculate the next announcement time");
+				continue;
+			}
 
-			info!("Start announcing about contents: {:?}", criteria);
-			let text = contents.iter()
-				.map(|c| c.announce(&criteria))
-				.flatten()
-				.collect::<Vec<String>>()
-				.join("\n\n");
+			if signals::shutdown_requested() {
+				info!("Stop announcing about contents for shutdown");
+				return;
+			}
 
-			if !text.is_empty() {
+			for announcement in self.announce_now() {
+				let text = announcement.render();
 				tx.send(Message::Status{
-					text,
-					visibility: Visibility::Public,
+					text: text.text().to_owned(),
+					spoiler_text: text.spoiler_text().map(|s| s.to_owned()),
+					language: text.language().map(|l| l.to_owned()),
+					visibility: announcement.visibility(),
 					mention: None,
 					in_reply_to_id: None,
 					poll_options: None,
+					priority: announcement.priority(),
+					context: LogContext::current(),
 				}).unwrap();
 			}
 
 			// Prevent runaway due to time error
 			thread::sleep(StdDuration::from_secs(10));
-		}});
+		}
 	}
 }
 
@@ -85,6 +179,14 @@ struct AnnouncementTimes {
 	inner: Vec<NaiveTime>,
 }
 
+impl std::ops::Deref for AnnouncementTimes {
+	type Target = Vec<NaiveTime>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
 impl AnnouncementTimes {
 	pub fn new(mut times: Vec<NaiveTime>) -> Self {
 		times.sort();
@@ -93,7 +195,7 @@ impl AnnouncementTimes {
 		}
 	}
 
-	pub fn duration_secs(&self, now: DateTime<Local>) -> u64 {
+	pub fn duration_secs(&self, now: DateTime<Tz>) -> u64 {
 		let t = match self.inner.iter()
 			.rev()
 			.find(|t| t > &&now.time())
@@ -112,43 +214,121 @@ impl AnnouncementTimes {
 #[derive(Debug, Clone, Deserialize)]
 struct Json {
 	announcement_times: Vec<NaiveTime>,
+	/// Names of the contents to announce about in the order.
+	#[serde(default = "registry::default_announcers")]
+	announcers: Vec<String>,
+}
+
+pub fn validate(validator: &mut Validator) {
+	let json: Json = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	validator.check_not_empty(DATA, "announcement_times", &json.announcement_times);
+	for name in json.announcers.iter().filter(|n| !registry::ANNOUNCERS.contains(&n.as_str())) {
+		validator.push(DATA, "announcers", format!("unknown announcer: {}", name));
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use chrono::offset::TimeZone;
+	use chrono_tz::Asia::Tokyo;
 
 	#[test]
 	fn test_duration_secs() {
 		let at = at();
-		let dt = Local.with_ymd_and_hms(2020, 9, 6, 6, 1, 30).unwrap();
+		let dt = Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 1, 30).unwrap();
 		assert_eq!(at.duration_secs(dt), 43200);
 
-		let dt = Local.with_ymd_and_hms(2020, 9, 6, 6, 1, 31).unwrap();
+		let dt = Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 1, 31).unwrap();
 		assert_eq!(at.duration_secs(dt), 43199);
 
-		let dt = Local.with_ymd_and_hms(2020, 9, 6, 6, 1, 29).unwrap();
+		let dt = Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 1, 29).unwrap();
 		assert_eq!(at.duration_secs(dt), 43201);
 
-		let dt = Local.timestamp_millis_opt(
-			Local.with_ymd_and_hms(2020, 9, 6, 6, 1, 29).unwrap().timestamp_millis() + 1
+		let dt = Tokyo.timestamp_millis_opt(
+			Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 1, 29).unwrap().timestamp_millis() + 1
 		).unwrap();
 		assert_eq!(at.duration_secs(dt), 43200);
 	}
 
+	#[test]
+	fn test_simulate() {
+		let contents = data(Vec::new());
+		let announcements = contents.simulate(
+			NaiveDate::from_ymd_opt(2020, 9, 6).unwrap(),
+			NaiveDate::from_ymd_opt(2020, 9, 7).unwrap(),
+		);
+
+		assert_eq!(
+			announcements.iter().map(|(at, _)| *at).collect::<Vec<DateTime<Tz>>>(),
+			vec![
+				Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 1, 30).unwrap(),
+				Tokyo.with_ymd_and_hms(2020, 9, 6, 18, 1, 30).unwrap(),
+				Tokyo.with_ymd_and_hms(2020, 9, 7, 6, 1, 30).unwrap(),
+				Tokyo.with_ymd_and_hms(2020, 9, 7, 18, 1, 30).unwrap(),
+			]
+		);
+		assert!(announcements.iter().all(|(_, announcements)| announcements.is_empty()));
+	}
+
 	fn at() -> AnnouncementTimes {
 		AnnouncementTimes::new(
 			serde_json::from_str::<Json>(DATA).unwrap().announcement_times
 		)
 	}
 
-	fn _data() -> ContentsWorker {
-		ContentsWorker {
-			contents: Arc::new(Vec::new()),
-			announcement_times: Arc::new(
-				AnnouncementTimes::new(serde_json::from_str::<Json>(DATA).unwrap().announcement_times)
-			)
+	#[test]
+	fn test_announce_start_of_jashin_period() {
+		let at = Tokyo.with_ymd_and_hms(2020, 7, 24, 18, 1, 30).unwrap();
+		let contents = data(vec![Box::new(jashin::tests::data())]);
+
+		let announcements = contents.announce(at);
+		assert_eq!(announcements.len(), 1);
+		assert!(announcements[0].text().starts_with("本日の邪神の宮殿は 五属性の災禍 です！明日からは "));
+
+		let at = at + Duration::hours(12);
+		assert_eq!(contents.announcement_times.duration_secs(at), 43200);
+
+		let announcements = contents.announce(at);
+		assert_eq!(announcements.len(), 1);
+		assert!(announcements[0].text().starts_with("邪神の宮殿は本日から "));
+		assert!(announcements[0].is_separate());
+	}
+
+	struct PanickingAnnouncer;
+
+	impl Announcer for PanickingAnnouncer {
+		fn name(&self) -> &'static str {
+			"panicking"
+		}
+
+		fn announce(&self, _criteria: &AnnouncementCriteria) -> Option<Announcement> {
+			panic!("bad data");
+		}
+	}
+
+	#[test]
+	fn test_announce_isolates_failure() {
+		let contents = data(vec![
+			Box::new(PanickingAnnouncer),
+			Box::new(jashin::tests::data()),
+		]);
+
+		let announcements = contents.announce(Tokyo.with_ymd_and_hms(2020, 7, 24, 18, 1, 30).unwrap());
+		assert_eq!(announcements.len(), 1);
+		assert!(announcements[0].text().starts_with("本日の邪神の宮殿は "));
+	}
+
+	fn data(contents: Vec<Box<dyn Announcer>>) -> Contents {
+		Contents {
+			contents,
+			announcement_times: AnnouncementTimes::new(
+				serde_json::from_str::<Json>(DATA).unwrap().announcement_times
+			),
 		}
 	}
 
@@ -1,6 +1,5 @@
 use std::fs::File;
 use std::io::BufReader;
-use std::sync::Arc;
 use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
@@ -14,15 +13,21 @@ use crate::{
 	Error,
 	Message,
 	Result,
+	config,
+	logging::LogContext,
+	message_processor::Priority,
+	signals,
+	supervisor::Worker,
 	tmp_file,
 	utils::transform_vec_string_to_vec_regex,
+	validation::Validator,
 };
 
-const DATA: &str = "drakeema-data/features/announcement/feeds.json";
+const DATA: &str = "features/announcement/feeds.json";
 
 #[derive(Debug, Clone)]
 pub struct FeedsWorker{
-	feeds: Arc<Feeds>,
+	feeds: Feeds,
 	announcement_interval_secs: u64,
 	post_interval_secs: u64,
 }
@@ -32,38 +37,49 @@ impl FeedsWorker {
 		info!("Initializing FeedWorker");
 
 		let json: FeedAnnouncementJson = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
 		Ok(FeedsWorker{
-			feeds: Arc::new(Feeds::new(json.feeds, json.title_regexes, json.user_agent)?),
+			feeds: Feeds::new(json.feeds, json.title_regexes, json.user_agent)?,
 			announcement_interval_secs: json.announcement_interval_secs,
 			post_interval_secs: json.post_interval_secs,
 		})
 	}
 
-	pub fn start(&self, tx: mpsc::Sender<Message>) {
-		let post_interval_secs = self.post_interval_secs;
-		let announcement_interval_secs = self.announcement_interval_secs;
+}
+
+impl Worker for FeedsWorker {
+	fn name(&self) -> &'static str {
+		"feeds"
+	}
 
-		let feeds = Arc::clone(&self.feeds);
-		thread::spawn(move || { loop {
+	fn run(&self, tx: mpsc::Sender<Message>) {
+		loop {
 			info!("Start announcing about feeds");
-			match feeds.fetch_entries() {
+			match self.feeds.fetch_entries() {
 				Ok(entries) => {
 					for entry in entries {
 						info!("Found entry to announce: {}", entry.title);
 
 						tx.send(Message::Status{
 							text: entry.build_text(),
+							spoiler_text: None,
+							language: None,
 							visibility: Visibility::Public,
 							mention: None,
 							in_reply_to_id: None,
 							poll_options: None,
+							priority: Priority::High,
+							context: LogContext::current(),
 						}).unwrap();
 
-						thread::sleep(Duration::from_secs(post_interval_secs));
+						// Entries already fetched are sent without intervals while shutting down,
+						// because the last checked ID has been saved and they are never fetched again.
+						if !signals::shutdown_requested() {
+							thread::sleep(Duration::from_secs(self.post_interval_secs));
+						}
 					}
 				},
 				Err(e) => {
@@ -71,9 +87,35 @@ impl FeedsWorker {
 				}
 			}
 
-			info!("Next announcement about feeds will be in {} secs", announcement_interval_secs);
-			thread::sleep(Duration::from_secs(announcement_interval_secs));
-		}});
+			if signals::shutdown_requested() {
+				info!("Stop announcing about feeds for shutdown");
+				return;
+			}
+
+			info!("Next announcement about feeds will be in {} secs", self.announcement_interval_secs);
+			thread::sleep(Duration::from_secs(self.announcement_interval_secs));
+		}
+	}
+}
+
+pub fn validate(validator: &mut Validator) {
+	let json: FeedAnnouncementJson = match validator.parse(DATA) {
+		Some(json) => json,
+		None => return,
+	};
+
+	validator.check_not_empty(DATA, "title_regexes", &json.title_regexes);
+	if json.announcement_interval_secs == 0 {
+		validator.push(DATA, "announcement_interval_secs", "announcement_interval_secs must be positive");
+	}
+
+	for (i, feed) in json.feeds.iter().enumerate() {
+		if feed.tmp.trim().is_empty() {
+			validator.push(DATA, format!("feeds[{}].tmp", i), "tmp must not be empty");
+		}
+		if json.feeds.iter().take(i).any(|f| f.tmp == feed.tmp) {
+			validator.push(DATA, format!("feeds[{}].tmp", i), format!("tmp is shared with another feed: {}", feed.tmp));
+		}
 	}
 }
 
@@ -1,5 +1,12 @@
 mod contents;
 mod feeds;
 
-pub use contents::ContentsWorker;
+pub use contents::{ Contents, ContentsWorker };
 pub use feeds::FeedsWorker;
+
+use crate::validation::Validator;
+
+pub fn validate(validator: &mut Validator) {
+	contents::validate(validator);
+	feeds::validate(validator);
+}
@@ -0,0 +1,150 @@
+use std::str::FromStr;
+use mastors::entities::Visibility;
+use regex::Regex;
+use serde::Deserialize;
+use crate::{
+	Result,
+	contents::{ Responder, Response, ResponseCriteria, Text },
+	message_processor::PollOptions,
+	utils::transform_string_to_regex,
+};
+
+const TAG_P_REGEX: &str = r#"</?[pP][^>]*>"#;
+const TAG_OTHER_REGEX: &str = r#"</?[^>]+>"#;
+
+/// Answer to "Can I?", which is sometimes a public poll instead of a reply.
+pub struct CanI {
+	config: CanIConfig,
+	keemasan_regex: Regex,
+	tag_p_regex: Regex,
+	tag_other_regex: Regex,
+}
+
+impl CanI {
+	pub fn new(config: CanIConfig, keemasan_regex: Regex) -> Result<Self> {
+		Ok(CanI {
+			config,
+			keemasan_regex,
+			tag_p_regex: Regex::from_str(TAG_P_REGEX)?,
+			tag_other_regex: Regex::from_str(TAG_OTHER_REGEX)?,
+		})
+	}
+
+	pub fn is_match(&self, text: &str) -> bool {
+		self.config.can_i_regex.is_match(text)
+	}
+
+	fn is_poll(&self, sec: u32) -> bool {
+		self.config.can_i_poll_secs.iter().any(|s| s == &sec)
+	}
+
+	fn format_for_poll(&self, text: &str) -> String {
+		let s = self.keemasan_regex.replace_all(text, "").to_string();
+		let s = self.tag_p_regex.replace_all(&s, "\n").to_string();
+		self.tag_other_regex.replace_all(&s, "").to_string()
+	}
+}
+
+impl Responder for CanI {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
+		use chrono::Timelike;
+
+		if !self.is_match(criteria.text()) {
+			return None;
+		}
+		info!("Text matched keywords of Can I?: {}", criteria.text());
+
+		let at = criteria.at();
+		if self.is_poll(at.second()) {
+			info!("Get the special response of Can I?: {}", at);
+
+			return Some(
+				Response::new(Text::new(self.format_for_poll(criteria.text())))
+					.with_visibility(Visibility::Public)
+					.without_mention()
+					.unthreaded()
+					.with_poll_options(PollOptions::new(
+						self.config.can_i_poll_options.clone(),
+						self.config.can_i_poll_expires_in,
+					))
+			);
+		}
+
+		self.config.can_i_responses.get(
+			at.second() as usize % self.config.can_i_responses.len()
+		)
+		.map(|r| Response::new(Text::new(r.as_str())))
+	}
+}
+
+/// Part of the status config about "Can I?".
+#[derive(Debug, Clone, Deserialize)]
+pub struct CanIConfig {
+	#[serde(deserialize_with = "transform_string_to_regex")]
+	can_i_regex: Regex,
+	pub(super) can_i_responses: Vec<String>,
+	pub(super) can_i_poll_secs: Vec<u32>,
+	pub(super) can_i_poll_options: Vec<String>,
+	can_i_poll_expires_in: u64,
+}
+
+impl CanIConfig {
+	pub(super) fn can_i_regex(&self) -> &Regex {
+		&self.can_i_regex
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::TimeZone;
+	use chrono_tz::Asia::Tokyo;
+
+	#[test]
+	fn test_respond() {
+		let can_i = CanI::new(
+			serde_json::from_str(DATA).unwrap(),
+			Regex::from_str("キーマさん").unwrap(),
+		).unwrap();
+		let content = "<p>キーマさん いいですか？</p>";
+
+		let reply = can_i.respond(&ResponseCriteria::new(
+			Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 40).unwrap(), content,
+		)).unwrap();
+		assert_eq!(reply.text(), "どうぞ！");
+		assert!(reply.mention());
+		assert!(reply.threaded());
+		assert!(reply.poll_options().is_none());
+
+		let poll = can_i.respond(&ResponseCriteria::new(
+			Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 41).unwrap(), content,
+		)).unwrap();
+		assert_eq!(poll.text(), "\n いいですか？\n");
+		assert_eq!(poll.visibility(), Some(Visibility::Public));
+		assert!(!poll.mention());
+		assert!(!poll.threaded());
+		assert!(poll.poll_options().is_some());
+
+		assert!(can_i.respond(&ResponseCriteria::new(
+			Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 40).unwrap(), "キーマさん こんにちは",
+		)).is_none());
+	}
+
+	const DATA: &str = r#"
+		{
+			"can_i_regex": "(?:いい|イイ|良い)(?:ですか|かな|の|かしら)*[!！]*[?？]",
+			"can_i_responses": [
+				"どうぞ！",
+				"ダメです！"
+			],
+			"can_i_poll_secs": [
+				41
+			],
+			"can_i_poll_options": [
+				"はい",
+				"いいえ"
+			],
+			"can_i_poll_expires_in": 300
+		}
+	"#;
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+use std::sync::mpsc;
+use chrono::{ Duration, Utc };
+use mastors::prelude::*;
+use mastors::api::v1::notifications;
+use crate::{
+	Error,
+	Result,
+	listeners::TimelineMessage,
+	tmp_file,
+};
+
+const TMP: &str = "last_notification_id.tmp";
+const FETCH_LIMIT: usize = 40;
+/// Notifications older than this are not answered when they are caught up.
+const MAX_AGE_SECS: i64 = 600;
+
+/// Fetch notifications which were missed while the streaming connection was lost,
+/// and send them to be processed in the same way as the streamed ones.
+pub fn catch_up(conn: &Connection, tx: &mpsc::Sender<TimelineMessage>) -> Result<()> {
+	let last_id = match NotificationCursor::load()?.last_id {
+		Some(last_id) => last_id,
+		None => {
+			info!("Skip catching up notifications: no notification has been processed yet");
+			return Ok(());
+		},
+	};
+
+	info!("Start catching up notifications since: {}", last_id);
+	let notifications = notifications::get(conn)
+		.since_id(last_id.as_str())
+		.limit(FETCH_LIMIT)
+		.send()?;
+
+	if notifications.len() >= FETCH_LIMIT {
+		warn!("Too many missed notifications, older ones are not caught up: {}", notifications.len());
+	}
+
+	let expired_at = Utc::now() - Duration::seconds(MAX_AGE_SECS);
+	let mut count = 0;
+
+	// Notifications are given from the newest one.
+	for notification in notifications.iter().rev() {
+		if notification.created_at() < &expired_at {
+			debug!("Skip catching up an old notification: {}", notification.id());
+			continue;
+		}
+
+		tx.send(TimelineMessage::MissedNotification(notification.clone()))
+			.map_err(|_| Error::ClosedChannel("missed notifications"))?;
+		count += 1;
+	}
+
+	info!("Catching up notifications is complete: {}", count);
+	Ok(())
+}
+
+/// ID of the last processed notification, saved in the temporary directory.
+#[derive(Debug, Clone)]
+pub struct NotificationCursor {
+	last_id: Option<String>,
+}
+
+impl NotificationCursor {
+	pub fn load() -> Result<Self> {
+		Ok(NotificationCursor {
+			last_id: tmp_file::load_tmp_as_string(TMP)?,
+		})
+	}
+
+	pub fn last_id(&self) -> Option<&str> {
+		self.last_id.as_deref()
+	}
+
+	/// Check whether the notification has not been processed yet.
+	pub fn is_new(&self, id: &str) -> bool {
+		match self.last_id.as_ref() {
+			Some(last_id) => compare_ids(id, last_id) == Ordering::Greater,
+			None => true,
+		}
+	}
+
+	pub fn save(&mut self, id: &str) -> Result<()> {
+		if self.is_new(id) {
+			tmp_file::save_tmp(TMP, id)?;
+			self.last_id = Some(id.to_owned());
+		}
+		Ok(())
+	}
+}
+
+/// Compare IDs of Mastodon which are numbers in string.
+pub(super) fn compare_ids(a: &str, b: &str) -> Ordering {
+	a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_compare_ids() {
+		assert_eq!(compare_ids("105000000000000001", "105000000000000000"), Ordering::Greater);
+		assert_eq!(compare_ids("99", "100"), Ordering::Less);
+		assert_eq!(compare_ids("100", "100"), Ordering::Equal);
+	}
+
+	#[test]
+	fn test_is_new() {
+		let cursor = NotificationCursor {
+			last_id: Some("100".to_owned()),
+		};
+		assert!(cursor.is_new("101"));
+		assert!(!cursor.is_new("100"));
+		assert!(!cursor.is_new("99"));
+
+		let cursor = NotificationCursor {
+			last_id: None,
+		};
+		assert!(cursor.is_new("1"));
+	}
+}
@@ -1,111 +1,411 @@
+mod can_i;
+mod catch_up;
 mod notification;
+mod polling;
+mod processed_ids;
 mod status;
 
-use std::sync::Arc;
+use std::sync::{ Arc, Mutex, MutexGuard };
+use std::sync::atomic::{ AtomicBool, Ordering };
 use std::sync::mpsc;
-use std::thread;
-use chrono::{ Duration, Local };
+use std::thread::{ self, JoinHandle };
+use std::time::Duration as StdDuration;
+use chrono::{ Duration, Utc };
 use mastors::prelude::*;
 use mastors::{
 	api::v1::accounts,
 	api::v1::streaming,
 };
-use crate::{ Error, Message, Result, };
+use crate::{
+	Error,
+	Message,
+	Result,
+	backoff::Backoff,
+	config,
+	logging::{ self, LogContext },
+	reload::{ DataSet, Swappable },
+	signals,
+	supervisor::Worker,
+	systemd::Liveness,
+	validation::Validator,
+};
 use crate::listeners::{
 	LocalTimelineListener,
+	Resumable,
 	UserTimelineListener,
 	TimelineMessage,
+	utils,
 };
-use notification::NotificationProcessor;
-use status::StatusProcessor;
+use catch_up::NotificationCursor;
+use polling::Poller;
+use processed_ids::{ Kind, ProcessedIds };
+
+pub use notification::NotificationProcessor;
+pub use status::StatusProcessor;
+
+pub fn validate(validator: &mut Validator) {
+	notification::validate(validator);
+	status::validate(validator);
+}
 
-const MAX_RETRY: usize = 5;
 const RETRY_RESET_INTERVAL_SECS: i64 = 300;
+const RETRY_BASE_SECS: u64 = 1;
+const RETRY_MAX_SECS: u64 = 300;
+/// Switch to polling after this number of retries without a stable streaming connection.
+const POLLING_AFTER_RETRY: usize = 3;
+const POLLING_INTERVAL_SECS: u64 = 30;
+/// Number of polls before trying to open a streaming connection again.
+const POLLS_PER_STREAMING_RETRY: usize = 10;
+
+const LOCAL_TIMELINE_LISTENER: &str = "local timeline listener";
+const USER_TIMELINE_LISTENER: &str = "user timeline listener";
+/// Names of the listeners which have to be alive while ResponseWorker is running.
+pub const LISTENERS: [&str; 2] = [LOCAL_TIMELINE_LISTENER, USER_TIMELINE_LISTENER];
 
 pub struct ResponseWorker {
 	me: Arc<Account>,
-	notification_processor: Arc<NotificationProcessor>,
-	status_processor: Arc<StatusProcessor>,
+	data: Arc<Swappable<DataSet>>,
+	listeners: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl ResponseWorker {
-	pub fn load() -> Result<Self> {
+	pub fn load(data: Arc<Swappable<DataSet>>) -> Result<Self> {
 		info!("Initialize ResponseWorker");
 
 		let conn = Connection::new()?;
 
 		Ok(ResponseWorker {
 			me: Arc::new(accounts::verify_credentials::get(&conn).send()?),
-			notification_processor: Arc::new(NotificationProcessor::load()?),
-			status_processor: Arc::new(StatusProcessor::load()?),
+			data,
+			listeners: Mutex::new(Vec::new()),
 		})
 	}
 
-	pub fn start(&self, tx: mpsc::Sender<Message>) {
-		let me = Arc::clone(&self.me);
-		let notification_processor = Arc::clone(&self.notification_processor);
-		let status_processor = Arc::clone(&self.status_processor);
+	/// Wait for the listeners of the previous run, which stop at their next event after the run ends.
+	fn join_listeners(&self) {
+		let handles = std::mem::take(&mut *self.listeners());
+		if !handles.is_empty() {
+			info!("Wait for the listeners of the previous run to stop: {}", handles.len());
+		}
+
+		for handle in handles {
+			if handle.join().is_err() {
+				error!("Listener of the previous run panicked");
+			}
+		}
+	}
+
+	fn listeners(&self) -> MutexGuard<Vec<JoinHandle<()>>> {
+		self.listeners.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	fn process_notification(
+		&self,
+		tx: &mpsc::Sender<Message>,
+		processed: &mut ProcessedIds,
+		cursor: &mut NotificationCursor,
+		notification: &Notification,
+	) {
+		if !mark_processed(processed, Kind::Notification, notification.id()) {
+			return;
+		}
+
+		self.data.load().notification_processor().process(tx, notification);
+
+		if let Err(e) = cursor.save(notification.id()) {
+			error!("Failed to save the last notification ID: {}", e);
+		}
+	}
+
+	fn process_status(
+		&self,
+		tx: &mpsc::Sender<Message>,
+		processed: &mut ProcessedIds,
+		status: &Status,
+	) {
+		if mark_processed(processed, Kind::Status, status.id()) {
+			self.data.load().status_processor().process(tx, status);
+		}
+	}
+
+	/// Respond to the mention which was missed, in the same way as the status on the timeline.
+	fn process_missed_mention(
+		&self,
+		tx: &mpsc::Sender<Message>,
+		processed: &mut ProcessedIds,
+		notification: &Notification,
+	) {
+		let status = match notification.status() {
+			Some(status) if notification.is_mention() => status,
+			_ => return,
+		};
+
+		if utils::is_mine(status, &self.me) || utils::has_spoiler_text(status) {
+			debug!("Skip missed mention: {}", status.id());
+			return;
+		}
+
+		info!("Respond to missed mention: {}", status.id());
+		self.process_status(tx, processed, status);
+	}
+}
+
+/// Create the log context with a new correlation ID for the event which has arrived.
+fn event_context(timeline_message: &TimelineMessage) -> LogContext {
+	let (status, account) = match timeline_message {
+		TimelineMessage::Notification(n) | TimelineMessage::MissedNotification(n) => (n.status(), n.account()),
+		TimelineMessage::Status(s) => (Some(&**s), s.account()),
+	};
+
+	LogContext {
+		status_id: status.map(|s| s.id().to_owned()),
+		acct: Some(account.acct().to_owned()),
+		..LogContext::new()
+	}
+}
+
+/// Record the ID as processed, and return `false` if it has already been processed.
+fn mark_processed(processed: &mut ProcessedIds, kind: Kind, id: &str) -> bool {
+	if !processed.insert(kind, id) {
+		info!("Skip {:?} already processed: {}", kind, id);
+		return false;
+	}
 
+	if let Err(e) = processed.save_if_due() {
+		error!("Failed to save processed IDs: {}", e);
+	}
+	true
+}
+
+impl Worker for ResponseWorker {
+	fn name(&self) -> &'static str {
+		"response"
+	}
+
+	/// Listen to the timelines and respond to them until both listeners stop.
+	/// When the run ends even by panic, the listeners stop at their next event,
+	/// and the next run waits for them before starting new ones.
+	fn run(&self, tx: mpsc::Sender<Message>) {
+		self.join_listeners();
+
+		let stop = Arc::new(AtomicBool::new(false));
+		let _stop_on_drop = StopOnDrop(Arc::clone(&stop));
+		let (inner_tx, inner_rx) = mpsc::channel();
+
+		let me_for_local = Arc::clone(&self.me);
+		let tx_for_local = mpsc::Sender::clone(&inner_tx);
 		let outer_tx_for_local = mpsc::Sender::clone(&tx);
+		let stop_for_local = Arc::clone(&stop);
+		let local = thread::Builder::new().name(LOCAL_TIMELINE_LISTENER.to_owned()).spawn(move || {
+			let _liveness = Liveness::new(LOCAL_TIMELINE_LISTENER);
+			let listener = LocalTimelineListener::new(me_for_local, tx_for_local);
+			if let Err(e) = listen(StreamType::PublicLocal, &listener, &stop_for_local, |_| ()) {
+				if outer_tx_for_local.send(Message::Error("Failed to connect to local timeline".to_owned(), e)).is_err() {
+					warn!("Failed to report the error of local timeline because the channel is closed");
+				}
+			}
+		});
+		match local {
+			Ok(local) => self.listeners().push(local),
+			Err(e) => {
+				error!("Failed to spawn {}: {}", LOCAL_TIMELINE_LISTENER, e);
+				return;
+			},
+		}
+
+		let me_for_user = Arc::clone(&self.me);
+		let tx_for_user = mpsc::Sender::clone(&inner_tx);
+		let tx_for_catch_up = mpsc::Sender::clone(&inner_tx);
 		let outer_tx_for_user = mpsc::Sender::clone(&tx);
-		thread::spawn(move || {
-			let (inner_tx, inner_rx) = mpsc::channel();
-
-			let me_for_local = Arc::clone(&me);
-			let tx_for_local = mpsc::Sender::clone(&inner_tx);
-			thread::spawn(move || {
-				let listener = LocalTimelineListener::new(me_for_local, tx_for_local);
-				if let Err(e) = listen(StreamType::PublicLocal, &listener) {
-					outer_tx_for_local.send(Message::Error("Failed to connect to local timeline".to_owned(), e)).unwrap();
+		let stop_for_user = Arc::clone(&stop);
+		let user = thread::Builder::new().name(USER_TIMELINE_LISTENER.to_owned()).spawn(move || {
+			let _liveness = Liveness::new(USER_TIMELINE_LISTENER);
+			let listener = UserTimelineListener::new(me_for_user, tx_for_user);
+			let on_connected = |conn: &Connection| {
+				if let Err(e) = catch_up::catch_up(conn, &tx_for_catch_up) {
+					error!("Failed to catch up notifications: {}", e);
 				}
-			});
-
-			let me_for_user = Arc::clone(&me);
-			let tx_for_user = mpsc::Sender::clone(&inner_tx);
-			thread::spawn(move || {
-				let listener = UserTimelineListener::new(me_for_user, tx_for_user);
-				if let Err(e) = listen(StreamType::User, &listener) {
-					outer_tx_for_user.send(Message::Error("Failed to connect to user timeline".to_owned(), e)).unwrap();
+			};
+			if let Err(e) = listen(StreamType::User, &listener, &stop_for_user, on_connected) {
+				if outer_tx_for_user.send(Message::Error("Failed to connect to user timeline".to_owned(), e)).is_err() {
+					warn!("Failed to report the error of user timeline because the channel is closed");
 				}
-			});
-
-			let tx_for_loop = mpsc::Sender::clone(&tx);
-			for timeline_message in inner_rx {
-				match timeline_message {
-					TimelineMessage::Notification(notification) => {
-						notification_processor.process(&tx_for_loop, &notification);
-					},
-					TimelineMessage::Status(status) => {
-						status_processor.process(&tx_for_loop, &status);
-					},
-				};
 			}
 		});
+		match user {
+			Ok(user) => self.listeners().push(user),
+			Err(e) => {
+				error!("Failed to spawn {}: {}", USER_TIMELINE_LISTENER, e);
+				return;
+			},
+		}
+		drop(inner_tx);
+
+		let mut cursor = match NotificationCursor::load() {
+			Ok(cursor) => cursor,
+			Err(e) => {
+				error!("Failed to load the last notification ID: {}", e);
+				return;
+			},
+		};
+
+		let mut processed = match ProcessedIds::load() {
+			Ok(processed) => processed,
+			Err(e) => {
+				error!("Failed to load processed IDs: {}", e);
+				return;
+			},
+		};
+
+		for timeline_message in inner_rx {
+			if signals::shutdown_requested() {
+				info!("Stop responding to timelines for shutdown");
+				break;
+			}
+
+			let _scope = logging::scope(event_context(&timeline_message));
+			info!("Timeline event arrived");
+
+			match timeline_message {
+				TimelineMessage::Notification(notification) => {
+					self.process_notification(&tx, &mut processed, &mut cursor, &notification);
+				},
+				TimelineMessage::MissedNotification(notification) => {
+					if processed.contains(Kind::Notification, notification.id()) {
+						continue;
+					}
+					self.process_missed_mention(&tx, &mut processed, &notification);
+					self.process_notification(&tx, &mut processed, &mut cursor, &notification);
+				},
+				TimelineMessage::Status(status) => {
+					self.process_status(&tx, &mut processed, &status);
+				},
+			};
+		}
 
+		if let Err(e) = processed.save() {
+			error!("Failed to save processed IDs: {}", e);
+		}
 	}
 }
 
-fn listen(
+/// Set the flag when dropped, so that the listeners stop when the run of the worker ends even by panic.
+struct StopOnDrop(Arc<AtomicBool>);
+
+impl Drop for StopOnDrop {
+	fn drop(&mut self) {
+		self.0.store(true, Ordering::SeqCst);
+	}
+}
+
+/// Attach the listener to the timeline, and open a new streaming connection whenever it is lost.
+/// When streaming keeps failing, the timeline is polled instead until streaming recovers,
+/// from the last item which the listener has received or the last processed notification.
+/// `on_connected` is called every time the connection is opened or polling is started.
+/// Listening ends when `stop` is set.
+fn listen<L>(
 	stream_type: StreamType,
-	listener: &impl EventListener,
-) -> Result<()> {
+	listener: &L,
+	stop: &AtomicBool,
+	on_connected: impl Fn(&Connection),
+) -> Result<()>
+where
+	L: EventListener + Resumable,
+	L::Error: std::fmt::Display,
+{
 	let conn = Connection::new()?;
-	let mut stream = streaming::get(&conn, stream_type.clone()).send()?;
+	let mut backoff = Backoff::new(
+		StdDuration::from_secs(RETRY_BASE_SECS),
+		StdDuration::from_secs(RETRY_MAX_SECS),
+	);
 	let mut retry = 0;
-	let mut last_retry = Local::now();
-	while retry < MAX_RETRY {
-		if let Err(e) = stream.attach(listener) {
-			if Local::now() - last_retry > Duration::seconds(RETRY_RESET_INTERVAL_SECS) {
-				retry = 1;
-			} else {
-				retry += 1;
-			}
-			warn!(
-				"Timeline listener returns an error: {}, timeline: {}, last_retry: {}, count: {}",
-				e, stream_type, last_retry, retry
-			);
-			last_retry = Local::now();
+	let mut lost_at = None;
+	let mut polling = false;
+	let mut poller = Poller::new(stream_type.clone());
+
+	if let StreamType::User = stream_type {
+		match NotificationCursor::load() {
+			Ok(cursor) => poller.resume(None, cursor.last_id()),
+			Err(e) => warn!("Failed to load the last notification ID for polling: {}", e),
+		};
+	}
+
+	loop {
+		if stop.load(Ordering::SeqCst) {
+			info!("Stop listening to timeline: {}", stream_type);
+			return Ok(());
+		}
+
+		match streaming::get(&conn, stream_type.clone()).send() {
+			Ok(mut stream) => {
+				if polling {
+					polling = false;
+					info!("Switch back to streaming from polling: {}", stream_type);
+				}
+
+				match lost_at.take() {
+					Some(lost_at) => info!(
+						"Reconnected to timeline: {}, outage: {} secs, retry: {}",
+						stream_type, (Utc::now() - lost_at).num_seconds(), retry
+					),
+					None => info!("Connected to timeline: {}", stream_type),
+				};
+				on_connected(&conn);
+
+				let connected_at = Utc::now();
+				match stream.attach(listener) {
+					Ok(_) => warn!("Timeline stream is closed: {}", stream_type),
+					Err(_) if stop.load(Ordering::SeqCst) => continue,
+					Err(e) => warn!("Timeline listener returns an error: {}, timeline: {}", e, stream_type),
+				};
+
+				if Utc::now() - connected_at > Duration::seconds(RETRY_RESET_INTERVAL_SECS) {
+					backoff.reset();
+					retry = 0;
+				}
+			},
+			Err(e) => warn!("Failed to connect to timeline: {}, {}", stream_type, e),
 		};
+
+		if lost_at.is_none() {
+			lost_at = Some(Utc::now());
+		}
+
+		retry += 1;
+		if let Some(max_retry) = config::get().max_retry() {
+			if retry > max_retry {
+				return Err(Error::LostStreamingConnection(stream_type, max_retry));
+			}
+		}
+
+		if retry >= POLLING_AFTER_RETRY {
+			if !polling {
+				polling = true;
+				warn!("Switch to polling because streaming keeps failing: {}, retry: {}", stream_type, retry);
+				on_connected(&conn);
+			}
+
+			let last_seen = listener.last_seen();
+			poller.resume(last_seen.status_id().as_deref(), last_seen.notification_id().as_deref());
+			for _ in 0 .. POLLS_PER_STREAMING_RETRY {
+				if stop.load(Ordering::SeqCst) {
+					break;
+				}
+				if let Err(e) = poller.poll(&conn, listener) {
+					warn!("Failed to poll timeline: {}, {}", stream_type, e);
+				}
+				thread::sleep(StdDuration::from_secs(POLLING_INTERVAL_SECS));
+			}
+
+			info!("Try to reconnect to timeline from polling: {}", stream_type);
+			continue;
+		}
+
+		let delay = backoff.next_delay_with_jitter();
+		warn!(
+			"Reconnect to timeline in {:.1} secs: {}, retry: {}",
+			delay.as_secs_f64(), stream_type, retry
+		);
+		thread::sleep(delay);
 	}
-	Err(Error::LostStreamingConnection(stream_type, MAX_RETRY))
 }
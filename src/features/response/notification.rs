@@ -3,10 +3,14 @@ use std::io::BufReader;
 use std::sync::mpsc;
 use mastors::entities::Notification;
 use serde::Deserialize;
-use crate::{ Error, Message, Result };
+use crate::{ Error, Message, Result, config };
+use crate::logging::LogContext;
+use crate::message_processor::Priority;
+use crate::reload::ReloadRequest;
 use crate::utils::transform_string_to_regex;
+use crate::validation::Validator;
 
-const DATA: &str = "drakeema-data/features/response/notification.json";
+const DATA: &str = "features/response/notification.json";
 
 #[derive(Debug, Clone)]
 pub struct NotificationProcessor {
@@ -18,7 +22,7 @@ impl NotificationProcessor {
 		info!("Initialize NotificationProcessor");
 
 		let config: NotificationConfig = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
@@ -39,27 +43,58 @@ impl NotificationProcessor {
 				None => return,
 			};
 
-			if self.config.follow_regex.is_match(content) {
+			if self.is_reload_request(notification.account().acct(), content) {
+				info!("Reloading data files is requested: {}", notification.account().acct());
+
+				// Safe unwrapping because is_reload_request() is true only when the config is given.
+				let reload = self.config.reload.as_ref().unwrap();
+				tx.send(Message::Reload(Some(ReloadRequest::new(
+					notification.account().acct(),
+					status.id(),
+					status.visibility(),
+					reload.reloaded_message.as_str(),
+					reload.failed_message.as_str(),
+				)))).unwrap();
+			} else if self.config.follow_regex.is_match(content) {
 				tx.send(Message::Follow(notification.account().clone())).unwrap();
 				tx.send(Message::Status{
 					text: self.config.followed_message.to_owned(),
+					spoiler_text: None,
+					language: None,
 					visibility: status.visibility(),
 					mention: Some(notification.account().acct().to_owned()),
 					in_reply_to_id: Some(status.id().to_owned()),
 					poll_options: None,
+					priority: Priority::Normal,
+					context: LogContext::current(),
 				}).unwrap();
 			} else if self.config.unfollow_regex.is_match(content) {
 				tx.send(Message::Unfollow(notification.account().clone())).unwrap();
 				tx.send(Message::Status{
 					text: self.config.unfollowed_message.to_owned(),
+					spoiler_text: None,
+					language: None,
 					visibility: status.visibility(),
 					mention: Some(notification.account().acct().to_owned()),
 					in_reply_to_id: Some(status.id().to_owned()),
 					poll_options: None,
+					priority: Priority::Normal,
+					context: LogContext::current(),
 				}).unwrap();
 			}
 		}
 	}
+
+	fn is_reload_request(&self, acct: &str, content: &str) -> bool {
+		match self.config.reload.as_ref() {
+			Some(reload) => reload.admin_accts.iter().any(|a| a == acct) && reload.regex.is_match(content),
+			None => false,
+		}
+	}
+}
+
+pub fn validate(validator: &mut Validator) {
+	let _: Option<NotificationConfig> = validator.parse(DATA);
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -71,5 +106,49 @@ struct NotificationConfig {
 	
 	followed_message: String,
 	unfollowed_message: String,
+
+	#[serde(default)]
+	reload: Option<ReloadConfig>,
+}
+
+/// Command to reload data files, which is accepted only from the admins.
+#[derive(Debug, Clone, Deserialize)]
+struct ReloadConfig {
+	admin_accts: Vec<String>,
+	#[serde(deserialize_with = "transform_string_to_regex")]
+	regex: regex::Regex,
+	reloaded_message: String,
+	failed_message: String,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_reload_request() {
+		let processor = NotificationProcessor {
+			config: serde_json::from_str(DATA).unwrap(),
+		};
+
+		assert!(processor.is_reload_request("kedama", "キーマさん リロードして"));
+		assert!(!processor.is_reload_request("kedama", "キーマさん フォローして"));
+		assert!(!processor.is_reload_request("kedama@example.com", "キーマさん リロードして"));
+	}
+
+	const DATA: &str = r#"
+		{
+			"follow_regex": "フォローして",
+			"unfollow_regex": "フォロー(?:外して|解除して)",
+			"followed_message": "フォローしました！",
+			"unfollowed_message": "フォローを外しました！",
+			"reload": {
+				"admin_accts": ["kedama"],
+				"regex": "リロードして",
+				"reloaded_message": "データを読み込み直しました！",
+				"failed_message": "データを読み込み直せませんでした……"
+			}
+		}
+	"#;
 }
 
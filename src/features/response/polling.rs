@@ -0,0 +1,312 @@
+use std::cmp::Ordering;
+use mastors::prelude::*;
+use mastors::api::v1::{
+	notifications,
+	timelines::{ home, public },
+};
+use crate::Result;
+use super::catch_up::compare_ids;
+
+const PAGE_LIMIT: usize = 40;
+
+/// Timelines which are read page by page through the REST API.
+pub trait Source {
+	/// Get statuses newer than `since_id` and older than `max_id`, from the newest one.
+	fn statuses(&self, stream_type: &StreamType, since_id: Option<&str>, max_id: Option<&str>) -> Result<Vec<Status>>;
+
+	/// Get notifications newer than `since_id` and older than `max_id`, from the newest one.
+	fn notifications(&self, since_id: Option<&str>, max_id: Option<&str>) -> Result<Vec<Notification>>;
+}
+
+impl Source for Connection {
+	fn statuses(&self, stream_type: &StreamType, since_id: Option<&str>, max_id: Option<&str>) -> Result<Vec<Status>> {
+		match stream_type {
+			StreamType::User => {
+				let request = home::get(self).limit(PAGE_LIMIT);
+				let request = match since_id {
+					Some(id) => request.since_id(id),
+					None => request,
+				};
+				let request = match max_id {
+					Some(id) => request.max_id(id),
+					None => request,
+				};
+				Ok(request.send()?.to_vec())
+			},
+			StreamType::PublicLocal => {
+				let request = public::get(self).local().limit(PAGE_LIMIT);
+				let request = match since_id {
+					Some(id) => request.since_id(id),
+					None => request,
+				};
+				let request = match max_id {
+					Some(id) => request.max_id(id),
+					None => request,
+				};
+				Ok(request.send()?.to_vec())
+			},
+			_ => Ok(Vec::new()),
+		}
+	}
+
+	fn notifications(&self, since_id: Option<&str>, max_id: Option<&str>) -> Result<Vec<Notification>> {
+		let request = notifications::get(self).limit(PAGE_LIMIT);
+		let request = match since_id {
+			Some(id) => request.since_id(id),
+			None => request,
+		};
+		let request = match max_id {
+			Some(id) => request.max_id(id),
+			None => request,
+		};
+		Ok(request.send()?.to_vec())
+	}
+}
+
+/// Poll the timelines through the REST API instead of the streaming API,
+/// and pass the new statuses and notifications to the listener as if they were streamed.
+#[derive(Debug)]
+pub struct Poller {
+	stream_type: StreamType,
+	status_since_id: Option<String>,
+	notification_since_id: Option<String>,
+}
+
+impl Poller {
+	pub fn new(stream_type: StreamType) -> Self {
+		Poller {
+			stream_type,
+			status_since_id: None,
+			notification_since_id: None,
+		}
+	}
+
+	/// Move the starting points of polling forward to the IDs which have been received in other ways such as streaming.
+	pub fn resume(&mut self, status_id: Option<&str>, notification_id: Option<&str>) {
+		advance(&mut self.status_since_id, status_id);
+		advance(&mut self.notification_since_id, notification_id);
+	}
+
+	/// Poll the timelines once, and pass all items after the starting points to the listener from the oldest one.
+	/// When no starting point is known, the poll only records the latest ID.
+	pub fn poll<S, L>(&mut self, source: &S, listener: &L) -> Result<()>
+	where
+		S: Source,
+		L: EventListener,
+		L::Error: std::fmt::Display,
+	{
+		debug!("Start polling timeline: {}", self.stream_type);
+
+		match self.stream_type {
+			StreamType::User => {
+				self.poll_statuses(source, listener)?;
+				self.poll_notifications(source, listener)?;
+			},
+			StreamType::PublicLocal => self.poll_statuses(source, listener)?,
+			_ => warn!("Polling is not supported: {}", self.stream_type),
+		};
+
+		Ok(())
+	}
+
+	fn poll_statuses<S, L>(&mut self, source: &S, listener: &L) -> Result<()>
+	where
+		S: Source,
+		L: EventListener,
+		L::Error: std::fmt::Display,
+	{
+		let since_id = match self.status_since_id.clone() {
+			Some(since_id) => since_id,
+			None => {
+				let statuses = source.statuses(&self.stream_type, None, None)?;
+				self.status_since_id = statuses.first().map(|s| s.id().to_owned());
+				return Ok(());
+			},
+		};
+
+		let statuses = fetch_all(
+			|max_id| source.statuses(&self.stream_type, Some(&since_id), max_id),
+			|status| status.id(),
+		)?;
+
+		for status in statuses.iter().rev() {
+			if let Err(e) = listener.update(status) {
+				error!("Failed to process polled status: {}, {}", status.id(), e);
+			}
+		}
+		advance(&mut self.status_since_id, statuses.first().map(|s| s.id()));
+
+		Ok(())
+	}
+
+	fn poll_notifications<S, L>(&mut self, source: &S, listener: &L) -> Result<()>
+	where
+		S: Source,
+		L: EventListener,
+		L::Error: std::fmt::Display,
+	{
+		let since_id = match self.notification_since_id.clone() {
+			Some(since_id) => since_id,
+			None => {
+				let notifications = source.notifications(None, None)?;
+				self.notification_since_id = notifications.first().map(|n| n.id().to_owned());
+				return Ok(());
+			},
+		};
+
+		let notifications = fetch_all(
+			|max_id| source.notifications(Some(&since_id), max_id),
+			|notification| notification.id(),
+		)?;
+
+		for notification in notifications.iter().rev() {
+			if let Err(e) = listener.notification(notification) {
+				error!("Failed to process polled notification: {}, {}", notification.id(), e);
+			}
+		}
+		advance(&mut self.notification_since_id, notifications.first().map(|n| n.id()));
+
+		Ok(())
+	}
+}
+
+/// Fetch pages from the newest one until an empty page is given, and return all items from the newest one.
+fn fetch_all<T>(
+	fetch: impl Fn(Option<&str>) -> Result<Vec<T>>,
+	id: impl Fn(&T) -> &str,
+) -> Result<Vec<T>> {
+	let mut items: Vec<T> = Vec::new();
+
+	loop {
+		let max_id = items.last().map(|item| id(item).to_owned());
+		let page = fetch(max_id.as_deref())?;
+
+		if page.is_empty() {
+			return Ok(items);
+		}
+		items.extend(page);
+	}
+}
+
+/// Replace the ID with the given one if it is newer.
+fn advance(since_id: &mut Option<String>, id: Option<&str>) {
+	if let Some(id) = id {
+		if since_id.as_deref().map_or(true, |since_id| compare_ids(id, since_id) == Ordering::Greater) {
+			*since_id = Some(id.to_owned());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::RefCell;
+	use chrono::TimeZone;
+	use chrono_tz::Asia::Tokyo;
+	use crate::test_support;
+
+	#[test]
+	fn test_first_poll_only_records_latest() {
+		let source = StubSource::new(&["3", "2", "1"]);
+		let listener = StubListener::default();
+		let mut poller = Poller::new(StreamType::PublicLocal);
+
+		poller.poll(&source, &listener).unwrap();
+		assert!(listener.received.borrow().is_empty());
+
+		source.push("4");
+		poller.poll(&source, &listener).unwrap();
+		assert_eq!(*listener.received.borrow(), vec!["status:4"]);
+	}
+
+	#[test]
+	fn test_poll_all_pages_since_last_seen() {
+		let source = StubSource::new(&["7", "6", "5", "4", "3", "2", "1"]);
+		let listener = StubListener::default();
+		let mut poller = Poller::new(StreamType::PublicLocal);
+
+		poller.resume(Some("2"), None);
+		poller.poll(&source, &listener).unwrap();
+		assert_eq!(
+			*listener.received.borrow(),
+			vec!["status:3", "status:4", "status:5", "status:6", "status:7"]
+		);
+
+		// Older IDs don't move the starting point back.
+		poller.resume(Some("5"), None);
+		source.push("8");
+		poller.poll(&source, &listener).unwrap();
+		assert_eq!(listener.received.borrow().last().map(|s| s.as_str()), Some("status:8"));
+		assert_eq!(listener.received.borrow().len(), 6);
+	}
+
+	#[test]
+	fn test_poll_notifications_since_cursor() {
+		let source = StubSource::new(&["12", "11", "10"]);
+		let listener = StubListener::default();
+		let mut poller = Poller::new(StreamType::User);
+
+		poller.resume(Some("12"), Some("10"));
+		poller.poll(&source, &listener).unwrap();
+		assert_eq!(*listener.received.borrow(), vec!["notification:11", "notification:12"]);
+	}
+
+	/// Timeline which has the same IDs for statuses and notifications, and gives 2 items per page.
+	struct StubSource {
+		ids: RefCell<Vec<String>>,
+	}
+
+	impl StubSource {
+		fn new(ids: &[&str]) -> Self {
+			StubSource {
+				ids: RefCell::new(ids.iter().map(|id| id.to_string()).collect()),
+			}
+		}
+
+		fn push(&self, id: &str) {
+			self.ids.borrow_mut().insert(0, id.to_owned());
+		}
+
+		fn page(&self, since_id: Option<&str>, max_id: Option<&str>) -> Vec<String> {
+			self.ids.borrow().iter()
+				.filter(|id| since_id.map_or(true, |since_id| compare_ids(id, since_id) == Ordering::Greater))
+				.filter(|id| max_id.map_or(true, |max_id| compare_ids(id, max_id) == Ordering::Less))
+				.take(2)
+				.cloned()
+				.collect()
+		}
+	}
+
+	impl Source for StubSource {
+		fn statuses(&self, _stream_type: &StreamType, since_id: Option<&str>, max_id: Option<&str>) -> Result<Vec<Status>> {
+			Ok(self.page(since_id, max_id).iter().map(|id| test_support::status(id, "キーマさん", at())).collect())
+		}
+
+		fn notifications(&self, since_id: Option<&str>, max_id: Option<&str>) -> Result<Vec<Notification>> {
+			Ok(self.page(since_id, max_id).iter().map(|id| test_support::notification(id, "キーマさん", at())).collect())
+		}
+	}
+
+	#[derive(Default)]
+	struct StubListener {
+		received: RefCell<Vec<String>>,
+	}
+
+	impl EventListener for StubListener {
+		type Error = crate::Error;
+
+		fn update(&self, status: &Status) -> std::result::Result<(), Self::Error> {
+			self.received.borrow_mut().push(format!("status:{}", status.id()));
+			Ok(())
+		}
+
+		fn notification(&self, notification: &Notification) -> std::result::Result<(), Self::Error> {
+			self.received.borrow_mut().push(format!("notification:{}", notification.id()));
+			Ok(())
+		}
+	}
+
+	fn at() -> chrono::DateTime<chrono_tz::Tz> {
+		Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 0).unwrap()
+	}
+}
@@ -0,0 +1,177 @@
+use std::collections::{ HashSet, VecDeque };
+use std::time::{ Duration, Instant };
+use crate::{
+	Error,
+	Result,
+	tmp_file,
+};
+
+const TMP: &str = "processed_ids.json";
+const CAPACITY: usize = 1000;
+/// IDs are saved when this number of IDs have been inserted since the last save,
+const SAVE_EVERY: usize = 20;
+/// or when this duration has passed since the last save.
+const SAVE_INTERVAL_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+	Status,
+	Notification,
+}
+
+impl Kind {
+	fn prefix(self) -> &'static str {
+		match self {
+			Kind::Status => "status",
+			Kind::Notification => "notification",
+		}
+	}
+}
+
+/// IDs of statuses and notifications which have been processed,
+/// the oldest ones are forgotten when the number of IDs exceeds the capacity.
+#[derive(Debug, Clone)]
+pub struct ProcessedIds {
+	capacity: usize,
+	order: VecDeque<String>,
+	inner: HashSet<String>,
+	unsaved: usize,
+	saved_at: Instant,
+}
+
+impl ProcessedIds {
+	pub fn load() -> Result<Self> {
+		let mut ids = ProcessedIds::new(CAPACITY);
+
+		if let Some(json) = tmp_file::load_tmp_as_string(TMP)? {
+			let keys: Vec<String> = serde_json::from_str(&json)
+				.map_err(|e| Error::UnparseableJson(TMP.to_owned(), e))?;
+			keys.into_iter().for_each(|key| ids.insert_key(key));
+		}
+
+		info!("Loaded processed IDs: {}", ids.order.len());
+		Ok(ids)
+	}
+
+	fn new(capacity: usize) -> Self {
+		ProcessedIds {
+			capacity,
+			order: VecDeque::with_capacity(capacity),
+			inner: HashSet::with_capacity(capacity),
+			unsaved: 0,
+			saved_at: Instant::now(),
+		}
+	}
+
+	pub fn contains(&self, kind: Kind, id: &str) -> bool {
+		self.inner.contains(&key(kind, id))
+	}
+
+	/// Record the ID as processed, and return `false` if it has already been processed.
+	pub fn insert(&mut self, kind: Kind, id: &str) -> bool {
+		let key = key(kind, id);
+
+		if self.inner.contains(&key) {
+			false
+		} else {
+			self.insert_key(key);
+			self.unsaved += 1;
+			true
+		}
+	}
+
+	/// Save the IDs only when enough IDs have been inserted or enough time has passed since the last save,
+	/// so that the file is not rewritten for every status on the timeline.
+	pub fn save_if_due(&mut self) -> Result<()> {
+		if self.is_due(Instant::now()) {
+			self.save()
+		} else {
+			Ok(())
+		}
+	}
+
+	pub fn save(&mut self) -> Result<()> {
+		if self.unsaved == 0 {
+			return Ok(());
+		}
+
+		let json = serde_json::to_string(&self.order)
+			.map_err(|e| Error::UnserializableJson(TMP.to_owned(), e))?;
+		tmp_file::save_tmp(TMP, json)?;
+
+		debug!("Saved processed IDs: {}, unsaved: {}", self.order.len(), self.unsaved);
+		self.unsaved = 0;
+		self.saved_at = Instant::now();
+		Ok(())
+	}
+
+	fn is_due(&self, now: Instant) -> bool {
+		self.unsaved >= SAVE_EVERY
+			|| (self.unsaved > 0 && now.saturating_duration_since(self.saved_at) >= Duration::from_secs(SAVE_INTERVAL_SECS))
+	}
+
+	fn insert_key(&mut self, key: String) {
+		if !self.inner.insert(key.clone()) {
+			return;
+		}
+		self.order.push_back(key);
+
+		while self.order.len() > self.capacity {
+			if let Some(oldest) = self.order.pop_front() {
+				self.inner.remove(&oldest);
+			}
+		}
+	}
+}
+
+fn key(kind: Kind, id: &str) -> String {
+	format!("{}:{}", kind.prefix(), id)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_insert() {
+		let mut ids = ProcessedIds::new(10);
+
+		assert!(ids.insert(Kind::Status, "100"));
+		assert!(!ids.insert(Kind::Status, "100"));
+		assert!(!ids.contains(Kind::Notification, "100"));
+		assert!(ids.insert(Kind::Notification, "100"));
+		assert!(!ids.insert(Kind::Notification, "100"));
+		assert!(ids.contains(Kind::Notification, "100"));
+	}
+
+	#[test]
+	fn test_is_due() {
+		let mut ids = ProcessedIds::new(100);
+		let now = ids.saved_at;
+
+		assert!(!ids.is_due(now + Duration::from_secs(SAVE_INTERVAL_SECS)));
+
+		ids.insert(Kind::Status, "1");
+		ids.insert(Kind::Status, "1");
+		assert!(!ids.is_due(now));
+		assert!(ids.is_due(now + Duration::from_secs(SAVE_INTERVAL_SECS)));
+
+		(2 .. SAVE_EVERY).for_each(|id| { ids.insert(Kind::Status, &id.to_string()); });
+		assert!(!ids.is_due(now));
+		ids.insert(Kind::Notification, "1");
+		assert!(ids.is_due(now));
+	}
+
+	#[test]
+	fn test_forget_oldest() {
+		let mut ids = ProcessedIds::new(2);
+
+		assert!(ids.insert(Kind::Status, "1"));
+		assert!(ids.insert(Kind::Status, "2"));
+		assert!(ids.insert(Kind::Status, "3"));
+
+		assert!(!ids.insert(Kind::Status, "3"));
+		assert!(!ids.insert(Kind::Status, "2"));
+		assert!(ids.insert(Kind::Status, "1"));
+	}
+}
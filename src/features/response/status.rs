@@ -1,179 +1,227 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::BufReader;
-use std::sync::mpsc;
-use chrono:: { Local, Timelike };
-use mastors::entities::{ Status, Visibility };
+use std::sync::{ Arc, mpsc };
+use chrono::Timelike;
+use mastors::entities::Status;
 use regex::Regex;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Monsters,
 	Result,
+	config,
+	clock::Clock,
 	contents::*,
+	logging::{ self, LogContext },
 	utils::{
+		transform_map_string_to_map_regex,
 		transform_string_to_regex,
 		transform_vec_string_to_vec_regex,
 	},
+	validation::Validator,
 };
 use crate::message_processor::{
 	Message,
-	PollOptions,
+	Priority,
 };
+use super::can_i::{ CanI, CanIConfig };
 
-const DATA: &str = "drakeema-data/features/response/status.json";
-const TAG_P_REGEX: &str = r#"</?[pP][^>]*>"#;
-const TAG_OTHER_REGEX: &str = r#"</?[^>]+>"#;
+const DATA: &str = "features/response/status.json";
 
 pub struct StatusProcessor {
+	clock: Arc<dyn Clock>,
 	responders: Vec<Box<dyn Responder>>,
+	can_i: CanI,
 	keema: Keema,
 	config: Config,
-	tag_p_regex: Regex,
-	tag_other_regex: Regex,
 }
 
 impl StatusProcessor {
-	pub fn load() -> Result<Self> {
-		use std::str::FromStr;
-
+	pub fn load(clock: Arc<dyn Clock>, monsters: &Monsters) -> Result<Self> {
 		info!("Initialize StatusProcessor");
 
 		let config: Config = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
-		let responders: Vec<Box<dyn Responder>> = vec![
-			Box::new(Jashin::load()?),
-			Box::new(Seishugosha::load()?),
-			Box::new(Panigulm::load()?),
-			Box::new(Konmeiko::load()?),
-			Box::new(Boueigun::load()?),
-			Box::new(Monsters::load()?),
-		];
+		let responders = config.responders.iter()
+			.map(|name| registry::load_responder(name, monsters))
+			.collect::<Result<Vec<Box<dyn Responder>>>>()?;
+		info!("Responders: {:?}", config.responders);
 
+		let can_i = CanI::new(config.can_i.clone(), config.keemasan_regex.clone())?;
 		let keema = Keema::load()?;
 
 		Ok(StatusProcessor {
+			clock,
 			responders,
+			can_i,
 			keema,
 			config,
-			tag_p_regex: Regex::from_str(TAG_P_REGEX)?,
-			tag_other_regex: Regex::from_str(TAG_OTHER_REGEX)?,
 		})
 	}
 
-	pub fn process(&self, tx: &mpsc::Sender<Message>, status: &Status) {
+	/// Respond to the status, and return the branch which built the response.
+	pub fn process(&self, tx: &mpsc::Sender<Message>, status: &Status) -> Option<Branch> {
         let content = match status.content() {
             Some(content) => content,
-            None => return,
+            None => return None,
         };
         trace!("Status received: {:?}", status);
 
 		if self.is_ignore(status.account().acct()) {
 			info!("Ignore status: acct: {}", status.account().acct());
-			return;
+			return None;
 		}
 
-		let text: Option<String>;
-		let mut visibility = status.visibility();
-		let mut mention = Some(status.account().acct().to_owned());
-		let mut in_reply_to_id = Some(status.id().to_owned());
-		let mut poll_options: Option<PollOptions> = None;
-
-        if self.is_oshiete_keemasan(content) {
-            info!("Text matched keywords of Oshiete: {}", content);
-
-			if status.account().is_local() && status.is_public() {
-				in_reply_to_id = None;
-			};
-
-			let rc = ResponseCriteria::new(Local::now(), content);
-            let mut t = self.responders.iter()
-                .map(|i| i.respond(&rc))
-				.flatten()
-                .collect::<Vec<String>>()
-                .join("\n");
-
-            if t.is_empty() {
-                t = String::from("？");
-            }
-
-			text = Some(t);
-		} else if self.is_keemasan(content) && self.is_healthcheck(content){
-			info!("Text matched keywords of healthcheck: {}", content);
-
-			text = self.config.healthcheck_responses.get(
-				Local::now().second() as usize % self.config.healthcheck_responses.len()
-			).map(|r| r.to_owned());
-		} else if self.is_keemasan(content) && self.can_i(content) {
-			info!("Text matched keywords of Can I?: {}", content);
-
-			let now = Local::now();
-			if self.is_poll(now.second()) {
-				info!("Get the special response of Can I?: {}", now);
-
-				text = Some(self.format_for_poll(content));
-				visibility = Visibility::Public;
-				mention = None;
-				in_reply_to_id = None;
-				poll_options = Some(PollOptions::new(
-					self.config.can_i_poll_options.clone(),
-					self.config.can_i_poll_expires_in,
-				));
-			} else {
-				text = self.config.can_i_responses.get(
-					now.second() as usize % self.config.can_i_responses.len()
+		let rule = match self.config.rules.iter().find(|r| r.enabled && self.is_match(r, content)) {
+			Some(rule) => rule,
+			None => {
+				debug!("No rule matched: {}", content);
+				return None;
+			},
+		};
+		info!("Text matched the rule: {:?}, {}", rule.requires, content);
+
+		let at = self.clock.now();
+		let criteria = ResponseCriteria::new(at, content);
+		let branch: Branch;
+		let response: Option<Response>;
+
+		match rule.handler {
+			Handler::Oshiete => {
+				let merged = Response::merge(
+					self.responders.iter().filter_map(|i| i.respond(&criteria)),
+					"\n",
 				)
-				.map(|r| r.to_owned());
-			}
-        } else {
-			text = self.keema.respond(&ResponseCriteria::new(Local::now(), content));
-        }
-
-        if let Some(text) = text {
-			tx.send(Message::Status {
-				text,
-				visibility,
-				mention,
-				in_reply_to_id,
-				poll_options,
-			}).unwrap();
-		}
+				.unwrap_or_else(|| Response::new(Text::new("？")));
+				let priority = merged.priority().unwrap_or(Priority::Normal);
+				let merged = merged.with_priority(priority);
+
+				response = if status.account().is_local() && status.is_public() {
+					Some(merged.unthreaded())
+				} else {
+					Some(merged)
+				};
+				branch = Branch::Oshiete;
+			},
+			Handler::Healthcheck => {
+				response = choose(&self.config.healthcheck_responses, at.second());
+				branch = Branch::Healthcheck;
+			},
+			Handler::CanI => {
+				response = self.can_i.respond(&criteria);
+				branch = match response.as_ref().and_then(|r| r.poll_options()) {
+					Some(_) => Branch::Poll,
+					None => Branch::CanI,
+				};
+			},
+			Handler::Keema => {
+				response = self.keema.respond(&criteria);
+				branch = Branch::Keema;
+			},
+			Handler::Responses => {
+				response = choose(&rule.responses, at.second());
+				branch = Branch::Responses;
+			},
+		};
+
+		logging::update(|c| c.responder = Some(branch.to_string()));
+		let response = response?;
+		tx.send(Message::Status {
+			text: response.text().to_owned(),
+			spoiler_text: response.spoiler_text().map(|s| s.to_owned()),
+			language: response.language().map(|l| l.to_owned()),
+			visibility: response.visibility().unwrap_or_else(|| status.visibility()),
+			mention: if response.mention() {
+				Some(status.account().acct().to_owned())
+			} else {
+				None
+			},
+			in_reply_to_id: if response.threaded() {
+				Some(status.id().to_owned())
+			} else {
+				None
+			},
+			poll_options: response.poll_options().cloned(),
+			priority: response.priority().unwrap_or(Priority::Low),
+			context: LogContext::current(),
+		}).unwrap();
+
+		Some(branch)
 	}
 
 	fn is_ignore(&self, acct: &str) -> bool {
 		self.config.ignore_acct_regex.iter().any(|re| re.is_match(acct))
 	}
 
-	fn is_healthcheck(&self, text: &str) -> bool {
-		self.config.healthcheck_regex.is_match(text)
+	/// Check whether the text matches all regexes which the rule requires.
+	fn is_match(&self, rule: &Rule, text: &str) -> bool {
+		rule.requires.iter().all(|name| self.config.regex(name).map_or(false, |re| re.is_match(text)))
 	}
+}
 
-	fn can_i(&self, text: &str) -> bool {
-		self.config.can_i_regex.is_match(text)
+/// Choose one of the responses by the second of the time.
+fn choose(responses: &[String], sec: u32) -> Option<Response> {
+	if responses.is_empty() {
+		return None;
 	}
+	responses.get(sec as usize % responses.len()).map(|r| Response::new(Text::new(r.as_str())))
+}
 
-	fn is_poll(&self, sec: u32) -> bool {
-		self.config.can_i_poll_secs.iter().any(|s| s == &sec)
-	}
+pub fn validate(validator: &mut Validator) {
+	let config: Config = match validator.parse(DATA) {
+		Some(config) => config,
+		None => return,
+	};
+
+	validator.check_not_empty(DATA, "healthcheck_responses", &config.healthcheck_responses);
+	validator.check_not_empty(DATA, "can_i_responses", &config.can_i.can_i_responses);
 
-	fn is_oshiete(&self, text: &str) -> bool {
-		self.config.oshiete_regex.is_match(text)
+	if config.can_i.can_i_poll_options.len() < 2 {
+		validator.push(DATA, "can_i_poll_options", "at least two options are required for a poll");
+	}
+	if let Some(sec) = config.can_i.can_i_poll_secs.iter().find(|s| **s >= 60) {
+		validator.push(DATA, "can_i_poll_secs", format!("second must be less than 60: {}", sec));
 	}
 
-	fn is_keemasan(&self, text: &str) -> bool {
-		self.config.keemasan_regex.is_match(text) 
+	for name in config.responders.iter().filter(|n| !registry::RESPONDERS.contains(&n.as_str())) {
+		validator.push(DATA, "responders", format!("unknown responder: {}", name));
 	}
 
-	fn is_oshiete_keemasan(&self, text: &str) -> bool {
-		self.is_oshiete(text) && self.is_keemasan(text)
+	for (i, rule) in config.rules.iter().enumerate() {
+		for name in rule.requires.iter().filter(|name| config.regex(name).is_none()) {
+			validator.push(DATA, format!("rules[{}].requires", i), format!("unknown regex: {}", name));
+		}
+		if rule.handler == Handler::Responses {
+			validator.check_not_empty(DATA, &format!("rules[{}].responses", i), &rule.responses);
+		}
 	}
+}
+
+/// Kind of the response to the status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+	Oshiete,
+	Healthcheck,
+	CanI,
+	Poll,
+	Keema,
+	Responses,
+}
 
-	fn format_for_poll(&self, text: &str) -> String {
-		let s = self.config.keemasan_regex.replace_all(text, "").to_string();
-		let s = self.tag_p_regex.replace_all(&s, "\n").to_string();
-		self.tag_other_regex.replace_all(&s, "").to_string()
+impl std::fmt::Display for Branch {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Branch::Oshiete => write!(f, "oshiete"),
+			Branch::Healthcheck => write!(f, "healthcheck"),
+			Branch::CanI => write!(f, "can_i"),
+			Branch::Poll => write!(f, "poll"),
+			Branch::Keema => write!(f, "keema"),
+			Branch::Responses => write!(f, "responses"),
+		}
 	}
 }
 
@@ -189,24 +237,98 @@ struct Config {
 	healthcheck_regex: Regex,
 	healthcheck_responses: Vec<String>,
 
-	#[serde(deserialize_with = "transform_string_to_regex")]
-	can_i_regex: Regex,
-	can_i_responses: Vec<String>,
-	can_i_poll_secs: Vec<u32>,
-	can_i_poll_options: Vec<String>,
-	can_i_poll_expires_in: u64,
+	#[serde(flatten)]
+	can_i: CanIConfig,
 
 	#[serde(deserialize_with = "transform_vec_string_to_vec_regex")]
 	ignore_acct_regex: Vec<Regex>,
+
+	/// Regexes which rules require by name, in addition to the built-in ones.
+	#[serde(default, deserialize_with = "transform_map_string_to_map_regex")]
+	regexes: HashMap<String, Regex>,
+
+	/// Names of the contents to respond about with oshiete in the order.
+	#[serde(default = "registry::default_responders")]
+	responders: Vec<String>,
+
+	/// Rules to choose the handler, the first enabled rule which matches the status is used.
+	#[serde(default = "default_rules")]
+	rules: Vec<Rule>,
+}
+
+impl Config {
+	/// Get the regex by the name used in rules, built-in names are
+	/// `keemasan`, `oshiete`, `healthcheck` and `can_i`.
+	fn regex(&self, name: &str) -> Option<&Regex> {
+		match name {
+			"keemasan" => Some(&self.keemasan_regex),
+			"oshiete" => Some(&self.oshiete_regex),
+			"healthcheck" => Some(&self.healthcheck_regex),
+			"can_i" => Some(self.can_i.can_i_regex()),
+			_ => self.regexes.get(name),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Rule {
+	handler: Handler,
+	/// Names of the regexes which the text must match all of.
+	#[serde(default)]
+	requires: Vec<String>,
+	#[serde(default = "enabled")]
+	enabled: bool,
+	/// Responses of the `responses` handler.
+	#[serde(default)]
+	responses: Vec<String>,
+}
+
+impl Rule {
+	fn new(handler: Handler, requires: &[&str]) -> Self {
+		Rule {
+			handler,
+			requires: requires.iter().map(|r| r.to_string()).collect(),
+			enabled: true,
+			responses: Vec::new(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Handler {
+	Oshiete,
+	Healthcheck,
+	CanI,
+	Keema,
+	Responses,
+}
+
+fn enabled() -> bool {
+	true
+}
+
+/// Rules used when status.json doesn't have them.
+fn default_rules() -> Vec<Rule> {
+	vec![
+		Rule::new(Handler::Oshiete, &["keemasan", "oshiete"]),
+		Rule::new(Handler::Healthcheck, &["keemasan", "healthcheck"]),
+		Rule::new(Handler::CanI, &["keemasan", "can_i"]),
+		Rule::new(Handler::Keema, &[]),
+	]
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use chrono::{ Duration, TimeZone };
+	use chrono_tz::Asia::Tokyo;
+	use crate::clock::{ FakeClock, SystemClock };
+	use crate::test_support;
 
 	#[test]
 	fn test_is_ignore() {
-		let resp = data(vec![]);
+		let resp = data(SystemClock::shared(), vec![]);
 
 		assert!(resp.is_ignore("hoge@example.com"));
 		assert!(resp.is_ignore("hoge@fuga.com"));
@@ -214,16 +336,55 @@ mod tests {
 		assert!(!resp.is_ignore("kedama@foresdon.jp"));
 	}
 
-	fn data(responders: Vec<Box<dyn Responder>>) -> StatusProcessor {
-		use std::str::FromStr;
+	#[test]
+	fn test_can_i_poll_at_second() {
+		let clock = FakeClock::new(Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 40).unwrap());
+		let resp = data(Arc::new(clock.clone()), vec![]);
+		let status = test_support::status("1", "キーマさん いいですか？", clock.now());
+		let (tx, _rx) = mpsc::channel();
+
+		assert_eq!(resp.process(&tx, &status), Some(Branch::CanI));
 
-		let config = serde_json::from_str::<Config>(DATA).unwrap();
+		clock.advance(Duration::seconds(1));
+		assert_eq!(resp.process(&tx, &status), Some(Branch::Poll));
+	}
+
+	#[test]
+	fn test_rules() {
+		let clock = FakeClock::new(Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 40).unwrap());
+		let mut config: serde_json::Value = serde_json::from_str(DATA).unwrap();
+		config["regexes"] = serde_json::json!({ "dance": "(?:踊|おど)って" });
+		config["rules"] = serde_json::json!([
+			{ "handler": "can_i", "requires": ["keemasan", "can_i"], "enabled": false },
+			{ "handler": "responses", "requires": ["keemasan", "dance"], "responses": ["💃"] },
+		]);
+		let resp = with_config(Arc::new(clock.clone()), serde_json::from_value(config).unwrap());
+		let (tx, rx) = mpsc::channel();
+
+		let status = test_support::status("1", "キーマさん 踊って", clock.now());
+		assert_eq!(resp.process(&tx, &status), Some(Branch::Responses));
+		match rx.try_recv() {
+			Ok(Message::Status { text, .. }) => assert_eq!(text, "💃"),
+			_ => panic!("status is not sent"),
+		};
+
+		let status = test_support::status("2", "キーマさん いいですか？", clock.now());
+		assert_eq!(resp.process(&tx, &status), None);
+	}
+
+	fn data(clock: Arc<dyn Clock>, responders: Vec<Box<dyn Responder>>) -> StatusProcessor {
+		let mut resp = with_config(clock, serde_json::from_str::<Config>(DATA).unwrap());
+		resp.responders = responders;
+		resp
+	}
+
+	fn with_config(clock: Arc<dyn Clock>, config: Config) -> StatusProcessor {
 		StatusProcessor {
-			responders,
+			clock,
+			responders: Vec::new(),
+			can_i: CanI::new(config.can_i.clone(), config.keemasan_regex.clone()).unwrap(),
 			keema: Keema::load().unwrap(),
 			config,
-			tag_p_regex: Regex::from_str(TAG_P_REGEX).unwrap(),
-			tag_other_regex: Regex::from_str(TAG_OTHER_REGEX).unwrap(),
 		}
 	}
 
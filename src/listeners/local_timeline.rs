@@ -3,12 +3,15 @@ use std::sync::mpsc::Sender;
 use mastors::prelude::*;
 use super::{
 	utils,
+	LastSeen,
+	Resumable,
 	TimelineMessage,
 };
 
 pub struct LocalTimelineListener {
 	me: Arc<Account>,
 	tx: Sender<TimelineMessage>,
+	last_seen: LastSeen,
 }
 
 impl LocalTimelineListener {
@@ -16,15 +19,23 @@ impl LocalTimelineListener {
 		LocalTimelineListener {
 			me,
 			tx,
+			last_seen: LastSeen::default(),
 		}
 	}
 }
 
+impl Resumable for LocalTimelineListener {
+	fn last_seen(&self) -> &LastSeen {
+		&self.last_seen
+	}
+}
+
 impl EventListener for LocalTimelineListener {
 	type Error = crate::Error;
 
 	fn update(&self, status: &Status) -> Result<(), Self::Error> {
 		debug!("Receive update: {:?}", status);
+		self.last_seen.see_status(status.id());
 
 		if utils::is_mine(status, &self.me) {
 			debug!("Skip update: Status posted by myself: {}", status.id());
@@ -41,7 +52,8 @@ impl EventListener for LocalTimelineListener {
 			return Ok(())
 		}
 
-		self.tx.send(TimelineMessage::Status(Box::new(status.clone()))).unwrap();
+		self.tx.send(TimelineMessage::Status(Box::new(status.clone())))
+			.map_err(|_| crate::Error::ClosedChannel("local timeline"))?;
 		Ok(())
 	}
 }
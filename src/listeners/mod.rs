@@ -5,6 +5,7 @@ pub(crate) mod utils;
 pub use user_timeline::UserTimelineListener;
 pub use local_timeline::LocalTimelineListener;
 
+use std::sync::Mutex;
 use mastors::entities::{
 	Status,
 	Notification,
@@ -14,5 +15,36 @@ use mastors::entities::{
 pub enum TimelineMessage {
 	Status(Box<Status>),
 	Notification(Notification),
+	/// Notification fetched after reconnection, which may have been missed.
+	MissedNotification(Notification),
 }
 
+/// Listener which remembers the IDs of the latest status and notification it has received,
+/// so that polling can resume from them when the streaming connection is lost.
+pub trait Resumable {
+	fn last_seen(&self) -> &LastSeen;
+}
+
+#[derive(Debug, Default)]
+pub struct LastSeen {
+	status_id: Mutex<Option<String>>,
+	notification_id: Mutex<Option<String>>,
+}
+
+impl LastSeen {
+	pub fn see_status(&self, id: &str) {
+		*self.status_id.lock().unwrap_or_else(|e| e.into_inner()) = Some(id.to_owned());
+	}
+
+	pub fn see_notification(&self, id: &str) {
+		*self.notification_id.lock().unwrap_or_else(|e| e.into_inner()) = Some(id.to_owned());
+	}
+
+	pub fn status_id(&self) -> Option<String> {
+		self.status_id.lock().unwrap_or_else(|e| e.into_inner()).clone()
+	}
+
+	pub fn notification_id(&self) -> Option<String> {
+		self.notification_id.lock().unwrap_or_else(|e| e.into_inner()).clone()
+	}
+}
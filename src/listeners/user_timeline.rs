@@ -3,12 +3,15 @@ use std::sync::mpsc;
 use mastors::prelude::*;
 use super::{
 	utils,
+	LastSeen,
+	Resumable,
 	TimelineMessage,
 };
 
 pub struct UserTimelineListener {
 	me: Arc<Account>, 
 	tx: mpsc::Sender<TimelineMessage>,
+	last_seen: LastSeen,
 }
 
 impl UserTimelineListener {
@@ -19,15 +22,23 @@ impl UserTimelineListener {
 		UserTimelineListener {
 			me,
 			tx,
+			last_seen: LastSeen::default(),
 		}
 	}
 }
 
+impl Resumable for UserTimelineListener {
+	fn last_seen(&self) -> &LastSeen {
+		&self.last_seen
+	}
+}
+
 impl EventListener for UserTimelineListener {
 	type Error = crate::Error;
 
 	fn update(&self, status: &Status) -> Result<(), Self::Error> {
 		debug!("Receive update: {:?}", status);
+		self.last_seen.see_status(status.id());
 
 
 		if utils::is_mine(status, &self.me) { 
@@ -50,13 +61,16 @@ impl EventListener for UserTimelineListener {
 			return Ok(());
 		}
 
-		self.tx.send(TimelineMessage::Status(Box::new(status.clone()))).unwrap();
+		self.tx.send(TimelineMessage::Status(Box::new(status.clone())))
+			.map_err(|_| crate::Error::ClosedChannel("user timeline"))?;
 		Ok(())
 	}
 
 	fn notification(&self, notification: &Notification) -> Result<(), Self::Error> {
 		debug!("Notification raceived: {}: {}", notification.notification_type(), notification.id());
-		self.tx.send(TimelineMessage::Notification(notification.clone())).unwrap();
+		self.last_seen.see_notification(notification.id());
+		self.tx.send(TimelineMessage::Notification(notification.clone()))
+			.map_err(|_| crate::Error::ClosedChannel("user timeline"))?;
 		Ok(())
 	}
 }
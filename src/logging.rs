@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::atomic::{ AtomicU64, Ordering };
+use std::thread;
+use chrono::{ SecondsFormat, Utc };
+use serde::{ Deserialize, Serialize };
+use serde_json::{ Map, Value };
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+	static CONTEXT: RefCell<LogContext> = RefCell::new(LogContext::default());
+}
+
+/// Format of the log lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+	Text,
+	Json,
+}
+
+impl std::str::FromStr for LogFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		match s {
+			"text" => Ok(LogFormat::Text),
+			"json" => Ok(LogFormat::Json),
+			_ => Err(format!("unknown log format: {}", s)),
+		}
+	}
+}
+
+/// Initialize the logger, the level is given by `RUST_LOG` as same as `env_logger`.
+pub fn init(format: LogFormat, timestamp: bool) {
+	let mut builder = env_logger::Builder::from_default_env();
+
+	match format {
+		LogFormat::Text => if !timestamp {
+			builder.format_timestamp(None);
+		},
+		LogFormat::Json => {
+			builder.format(move |buf, record| {
+				let line = json_line(record, timestamp);
+				writeln!(buf, "{}", line)
+			});
+		},
+	};
+
+	builder.init();
+}
+
+/// Fields attached to the log lines written while processing one timeline event or one message.
+/// The context is carried through `Message` so that the whole life of a reply has the same correlation ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogContext {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub correlation_id: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub status_id: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub acct: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub responder: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub posted_id: Option<String>,
+}
+
+impl LogContext {
+	/// Create a context with a new correlation ID.
+	pub fn new() -> Self {
+		LogContext {
+			correlation_id: Some(new_correlation_id()),
+			..Default::default()
+		}
+	}
+
+	/// Get the context of the current thread.
+	pub fn current() -> Self {
+		CONTEXT.with(|c| c.borrow().clone())
+	}
+
+	/// Give a new correlation ID if the context doesn't have it yet.
+	pub fn or_new_correlation_id(mut self) -> Self {
+		if self.correlation_id.is_none() {
+			self.correlation_id = Some(new_correlation_id());
+		}
+		self
+	}
+}
+
+/// Set the context of the current thread until the returned guard is dropped.
+pub fn scope(context: LogContext) -> ScopeGuard {
+	let previous = CONTEXT.with(|c| c.replace(context));
+	ScopeGuard {
+		previous: Some(previous),
+	}
+}
+
+/// Modify the context of the current thread.
+pub fn update(f: impl FnOnce(&mut LogContext)) {
+	CONTEXT.with(|c| f(&mut c.borrow_mut()));
+}
+
+/// Restore the previous context when dropped.
+#[derive(Debug)]
+pub struct ScopeGuard {
+	previous: Option<LogContext>,
+}
+
+impl Drop for ScopeGuard {
+	fn drop(&mut self) {
+		if let Some(previous) = self.previous.take() {
+			CONTEXT.with(|c| c.replace(previous));
+		}
+	}
+}
+
+fn new_correlation_id() -> String {
+	format!(
+		"{}-{:06}",
+		Utc::now().format("%Y%m%d%H%M%S%3f"),
+		SEQUENCE.fetch_add(1, Ordering::SeqCst) % 1_000_000,
+	)
+}
+
+fn json_line(record: &log::Record, timestamp: bool) -> String {
+	let mut fields = Map::new();
+
+	if timestamp {
+		fields.insert("time".to_owned(), Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)));
+	}
+	fields.insert("level".to_owned(), Value::from(record.level().to_string()));
+	fields.insert("target".to_owned(), Value::from(record.target()));
+	fields.insert("component".to_owned(), Value::from(thread::current().name().unwrap_or("unnamed")));
+	fields.insert("message".to_owned(), Value::from(record.args().to_string()));
+
+	if let Ok(Value::Object(context)) = serde_json::to_value(LogContext::current()) {
+		fields.extend(context);
+	}
+
+	Value::Object(fields).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_scope() {
+		let context = LogContext {
+			acct: Some("kedama".to_owned()),
+			..LogContext::new()
+		};
+
+		{
+			let _scope = scope(context.clone());
+			update(|c| c.responder = Some("keema".to_owned()));
+
+			let current = LogContext::current();
+			assert_eq!(current.correlation_id, context.correlation_id);
+			assert_eq!(current.acct.as_deref(), Some("kedama"));
+			assert_eq!(current.responder.as_deref(), Some("keema"));
+		}
+
+		assert_eq!(LogContext::current(), LogContext::default());
+	}
+
+	#[test]
+	fn test_json_line() {
+		let _scope = scope(LogContext {
+			correlation_id: Some("20200906060130000-000001".to_owned()),
+			status_id: Some("12345".to_owned()),
+			..Default::default()
+		});
+
+		let line = json_line(
+			&log::Record::builder()
+				.args(format_args!("Status received"))
+				.level(log::Level::Info)
+				.target("drakeema")
+				.build(),
+			false,
+		);
+		let json: Value = serde_json::from_str(&line).unwrap();
+
+		assert_eq!(json["level"], "INFO");
+		assert_eq!(json["message"], "Status received");
+		assert_eq!(json["correlation_id"], "20200906060130000-000001");
+		assert_eq!(json["status_id"], "12345");
+		assert!(json.get("time").is_none());
+		assert!(json.get("acct").is_none());
+	}
+}
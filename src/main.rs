@@ -4,16 +4,33 @@ extern crate log;
 #[macro_use]
 extern crate lazy_static;
 
+pub(crate) mod backoff;
+pub(crate) mod clock;
+pub(crate) mod commands;
+pub(crate) mod config;
 pub(crate) mod contents;
 pub(crate) mod emojis;
 pub(crate) mod error;
+pub(crate) mod fake;
 pub(crate) mod features;
 pub(crate) mod listeners;
+pub(crate) mod logging;
 pub(crate) mod monsters;
+pub(crate) mod outbox;
+pub(crate) mod outputs;
 pub(crate) mod rate_limit;
+pub(crate) mod reload;
 pub(crate) mod resistances;
+pub(crate) mod signals;
+pub(crate) mod status_text;
+pub(crate) mod supervisor;
+pub(crate) mod systemd;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub(crate) mod timezone;
 pub(crate) mod tmp_file;
 pub(crate) mod utils;
+pub(crate) mod validation;
 pub(crate) mod message_processor;
 
 pub(crate) use error::{ Error, Result };
@@ -22,6 +39,7 @@ pub(crate) use message_processor::Message;
 
 use std::process;
 use std::sync::mpsc;
+use std::time::{ Duration, Instant };
 use mastors::prelude::*;
 use features::announcement::{
 	ContentsWorker,
@@ -29,58 +47,119 @@ use features::announcement::{
 };
 use features::response::ResponseWorker;
 use message_processor::MessageProcessor;
+use reload::Reloader;
+use clock::SystemClock;
+use supervisor::Supervisor;
 
-lazy_static! {
-	static ref MONSTERS: Monsters = {
-		monsters::Monsters::load().unwrap()
-	};
-}
-
-fn monsters() -> &'static Monsters {
-	&MONSTERS
-}
+/// Shutdown must finish before systemd kills the process with `TimeoutSec=15`.
+const SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+/// Workers are regarded as stopped when no message arrives for this duration while shutting down.
+const SHUTDOWN_IDLE_MILLIS: u64 = 500;
+/// The message loop is regarded as hung when it doesn't come back within this duration.
+const MESSAGE_LOOP_TIMEOUT_SECS: u64 = 60;
+const MESSAGE_LOOP: &str = "message loop";
 
 fn main() {
-	if parse_args().is_present("notime") {
-		env_logger::builder().format_timestamp(None).init();
-	} else {
-		env_logger::init();
-	}
+	let args = parse_args();
+
+	// Safe unwrapping because clap accepts only the possible values.
+	let log_format: logging::LogFormat = args.value_of("logformat").unwrap().parse().unwrap();
+	logging::init(log_format, !args.is_present("notime"));
 	info!("Start drakeema: {}", env!("CARGO_PKG_VERSION"));
 
-	let contents_worker = match ContentsWorker::load() {
-		Ok(cw) => cw,
+	let mut config = match config::Config::load(args.value_of("config")) {
+		Ok(config) => config,
 		Err(e) => {
-			error!("Fatal error occurred while initialize ContentsWorker: {}", e);
+			error!("Fatal error occurred while load the configuration: {}", e);
 			process::exit(1);
 		},
 	};
+	if let Some(dir) = args.value_of("datadir") {
+		config.set_data_dir(dir);
+	}
+	info!("Data directory: {:?}, state directory: {:?}", config.data_dir(), config.state_dir());
+	if let Err(e) = config::init(config) {
+		error!("Fatal error occurred while initialize the configuration: {}", e);
+		process::exit(1);
+	}
 
-	let feeds_worker = match FeedsWorker::load() {
-		Ok(fw) => fw,
+	match timezone::load() {
+		Ok(tz) => info!("Timezone of the game: {}", tz),
 		Err(e) => {
-			error!("Fatal error occurred while initialize FeedsWorker: {}", e);
+			error!("Fatal error occurred while load the timezone: {}", e);
 			process::exit(1);
 		},
 	};
 
-	let response_worker = match ResponseWorker::load() {
-		Ok(rw) => rw,
+	if args.subcommand_matches("validate").is_some() {
+		process::exit(commands::validate::run());
+	}
+
+	if let Some(sub_args) = args.subcommand_matches("ask") {
+		process::exit(commands::ask::run(sub_args));
+	}
+
+	if let Some(sub_args) = args.subcommand_matches("simulate") {
+		process::exit(commands::simulate::run(sub_args));
+	}
+
+	let clock = SystemClock::shared();
+	let workers = config::get().workers();
+
+	let reloader = match Reloader::load(std::sync::Arc::clone(&clock)) {
+		Ok(reloader) => reloader,
 		Err(e) => {
-			error!("Fatal error occurred while initialize ResponseWorker: {}", e);
+			error!("Fatal error occurred while load data files: {}", e);
 			process::exit(1);
 		},
 	};
 
-	let conn = match Connection::new() {
-		Ok(conn) => conn,
-		Err(e) => {
-			error!("Fatal error occurred while create Connection for mastors: {}", e);
-			process::exit(1);
-		},
+	let contents_worker = if workers.contents() {
+		Some(ContentsWorker::new(std::sync::Arc::clone(&clock), reloader.data()))
+	} else {
+		info!("ContentsWorker is disabled");
+		None
+	};
+
+	let feeds_worker = if workers.feeds() {
+		match FeedsWorker::load() {
+			Ok(fw) => Some(fw),
+			Err(e) => {
+				error!("Fatal error occurred while initialize FeedsWorker: {}", e);
+				process::exit(1);
+			},
+		}
+	} else {
+		info!("FeedsWorker is disabled");
+		None
+	};
+
+	let response_worker = if workers.response() {
+		match ResponseWorker::load(reloader.data()) {
+			Ok(rw) => Some(rw),
+			Err(e) => {
+				error!("Fatal error occurred while initialize ResponseWorker: {}", e);
+				process::exit(1);
+			},
+		}
+	} else {
+		info!("ResponseWorker is disabled");
+		None
+	};
+
+	let conn = if args.is_present("dryrun") {
+		None
+	} else {
+		match Connection::new() {
+			Ok(conn) => Some(conn),
+			Err(e) => {
+				error!("Fatal error occurred while create Connection for mastors: {}", e);
+				process::exit(1);
+			},
+		}
 	};
 
-	let mut processor = match MessageProcessor::new(&conn) {
+	let mut processor = match MessageProcessor::new(conn.as_ref(), clock) {
 		Ok(mp) => mp,
 		Err(e) => {
 			error!("Fatal error occurred while initialize MessageProcessor: {}", e);
@@ -88,15 +167,60 @@ fn main() {
 		},
 	};
 
+	if let Err(e) = processor.resend_pending() {
+		error!("A fatal error has occurred while sending queued messages: {}", e);
+		process::exit(9);
+	}
+
 	let (tx, rx) = mpsc::channel();
 
-	contents_worker.start(mpsc::Sender::clone(&tx));
-	feeds_worker.start(mpsc::Sender::clone(&tx));
-	response_worker.start(mpsc::Sender::clone(&tx));
+	if let Err(e) = signals::listen(mpsc::Sender::clone(&tx)) {
+		error!("Fatal error occurred while listen to signals: {}", e);
+		process::exit(1);
+	}
 
-	for message in rx {
-		if let Err(e) = processor.process(message) {
-			error!("A fatal error has occurred while processing message: {}", e);
+	let mut expected = vec![MESSAGE_LOOP];
+	let supervisor = Supervisor::new(mpsc::Sender::clone(&tx));
+	if let Some(worker) = contents_worker {
+		supervisor.spawn(worker);
+	}
+	if let Some(worker) = feeds_worker {
+		supervisor.spawn(worker);
+	}
+	if let Some(worker) = response_worker {
+		supervisor.spawn(worker);
+		expected.extend(features::response::LISTENERS.iter());
+	}
+
+	let liveness = systemd::Liveness::with_timeout(MESSAGE_LOOP, Duration::from_secs(MESSAGE_LOOP_TIMEOUT_SECS));
+	if let Err(e) = systemd::watch(expected) {
+		error!("Fatal error occurred while start pinging watchdog: {}", e);
+		process::exit(1);
+	}
+	systemd::ready();
+	info!("drakeema is ready");
+
+	loop {
+		liveness.beat();
+
+		match rx.recv_timeout(processor.idle_timeout()) {
+			Ok(Message::Reload(request)) => if let Some(reply) = reloader.reload_for(request) {
+				tx.send(reply).unwrap();
+			},
+			Ok(Message::Shutdown) => {
+				shutdown(&mut processor, &rx);
+				break;
+			},
+			Ok(message) => if let Err(e) = processor.process(message) {
+				error!("A fatal error has occurred while processing message: {}", e);
+				process::exit(9);
+			},
+			Err(mpsc::RecvTimeoutError::Timeout) => (),
+			Err(mpsc::RecvTimeoutError::Disconnected) => break,
+		};
+
+		if let Err(e) = processor.resend_pending() {
+			error!("A fatal error has occurred while sending queued messages: {}", e);
 			process::exit(9);
 		}
 	}
@@ -105,6 +229,29 @@ fn main() {
 	process::exit(0);
 }
 
+/// Process the messages which workers are sending at the moment, then send or save the pending ones.
+/// New jobs are not taken by workers because the shutdown has already been requested.
+fn shutdown(processor: &mut MessageProcessor, rx: &mpsc::Receiver<Message>) {
+	info!("Start shutting down drakeema");
+	systemd::stopping();
+	systemd::status("Shutting down");
+
+	let deadline = Instant::now() + Duration::from_secs(SHUTDOWN_TIMEOUT_SECS);
+	while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+		match rx.recv_timeout(std::cmp::min(timeout, Duration::from_millis(SHUTDOWN_IDLE_MILLIS))) {
+			Ok(Message::Reload(_)) | Ok(Message::Shutdown) => (),
+			Ok(message) => if let Err(e) = processor.process(message) {
+				error!("Failed to process message while shutting down: {}", e);
+			},
+			Err(_) => break,
+		};
+	}
+
+	if let Err(e) = processor.shutdown() {
+		error!("Failed to send pending messages while shutting down: {}", e);
+	}
+}
+
 fn parse_args<'a>() -> clap::ArgMatches<'a> {
     clap::App::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
@@ -130,5 +277,91 @@ fn parse_args<'a>() -> clap::ArgMatches<'a> {
             .args(&["time", "notime"])
             .required(false)
         )
+        .arg(
+            clap::Arg::with_name("logformat")
+                .long("log-format")
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .env("DRAKEEMA_LOG_FORMAT")
+                .help("Format of the log, json lines have the correlation ID of each reply")
+        )
+        .arg(
+            clap::Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .takes_value(true)
+                .global(true)
+                .value_name("FILE")
+                .help("Configuration file, drakeema.json in the working directory is used if it exists")
+        )
+        .arg(
+            clap::Arg::with_name("datadir")
+                .short("d")
+                .long("data-dir")
+                .takes_value(true)
+                .global(true)
+                .value_name("DIR")
+                .help("Root directory of the data files, overrides the configuration file and DRAKEEMA_DATA_DIR")
+        )
+        .arg(
+            clap::Arg::with_name("dryrun")
+                .short("n")
+                .long("dry-run")
+                .help("Write statuses, follows and unfollows to the standard output as JSON lines instead of sending them")
+        )
+        .subcommand(
+            clap::SubCommand::with_name("validate")
+                .about("Load all data files and report problems found in them")
+        )
+        .subcommand(
+            clap::SubCommand::with_name("ask")
+                .about("Print the response of the bot to the text without connecting to the server")
+                .arg(
+                    clap::Arg::with_name("text")
+                        .required(true)
+                        .help("Text of the status mentioned to the bot")
+                )
+                .arg(
+                    clap::Arg::with_name("acct")
+                        .long("acct")
+                        .takes_value(true)
+                        .help("Acct of the fake account which posts the status")
+                )
+                .arg(
+                    clap::Arg::with_name("visibility")
+                        .long("visibility")
+                        .takes_value(true)
+                        .possible_values(&["public", "unlisted", "private", "direct"])
+                        .help("Visibility of the status")
+                )
+                .arg(
+                    clap::Arg::with_name("at")
+                        .long("at")
+                        .takes_value(true)
+                        .value_name("RFC3339")
+                        .help("Time when the status is received, such as 2020-09-06T06:00:00+09:00")
+                )
+        )
+        .subcommand(
+            clap::SubCommand::with_name("simulate")
+                .about("Print announcements about contents at every announcement time between the dates")
+                .arg(
+                    clap::Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("YYYY-MM-DD")
+                        .help("First date to simulate")
+                )
+                .arg(
+                    clap::Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("YYYY-MM-DD")
+                        .help("Last date to simulate")
+                )
+        )
         .get_matches()
 }
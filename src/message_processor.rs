@@ -1,140 +1,443 @@
+use std::collections::{ HashSet, VecDeque };
+use std::time::Duration;
+use std::sync::Arc;
 use mastors::prelude::*;
-use mastors::api::v1::{
-	statuses,
-	accounts::id::{ follow, unfollow },
-};
+use mastors::api::v1::instance;
+use serde::{ Deserialize, Serialize };
 use crate::Result;
+use crate::clock::Clock;
+use crate::config;
 use crate::emojis::Emojis;
+use crate::logging::{ self, LogContext };
+use crate::outbox::{ Letter, Outbox };
+use crate::outputs::{
+	MastodonOutput,
+	Output,
+	Post,
+	StdoutOutput,
+};
 use crate::rate_limit::RateLimit;
+use crate::reload::ReloadRequest;
+use crate::status_text;
+
+const QUEUE_CAPACITY: usize = 50;
+const MAX_IDLE_SECS: u64 = 10;
 
 pub struct MessageProcessor<'a> {
-	conn: &'a Connection,
+	clock: Arc<dyn Clock>,
+	output: Box<dyn Output + 'a>,
 	emojis: Emojis<'a>,
 	limit_for_status: RateLimit,
 	limit_for_ff: RateLimit,
+	max_characters: usize,
+	outbox: Outbox,
+	queue: VecDeque<Job>,
+	queued_letters: HashSet<String>,
 }
 
 impl<'a> MessageProcessor<'a> {
-	pub fn new(conn: &'a Connection) -> Result<Self> {
+	/// Create a processor that sends messages to the server,
+	/// or writes them to the standard output when the connection is not given.
+	pub fn new(conn: Option<&'a Connection>, clock: Arc<dyn Clock>) -> Result<Self> {
 		info!("Initialize MessageProcessor");
 
-		Ok(MessageProcessor {
-			conn,
-			emojis: Emojis::load(conn)?,
-			limit_for_status: RateLimit::new(20),
-			limit_for_ff: RateLimit::new(10),
-		})
+		let (output, emojis, outbox, max_characters): (Box<dyn Output + 'a>, Emojis<'a>, Outbox, usize) = match conn {
+			Some(conn) => (
+				Box::new(MastodonOutput::new(conn)),
+				Emojis::load(conn)?,
+				Outbox::load(Arc::clone(&clock))?,
+				max_characters(conn),
+			),
+			None => {
+				info!("MessageProcessor is running in dry-run mode");
+				(
+					Box::new(StdoutOutput::new()),
+					Emojis::load_cached()?,
+					Outbox::load_dry_run(Arc::clone(&clock))?,
+					config::get().max_characters(),
+				)
+			},
+		};
+
+		Ok(Self::with_output(output, emojis, outbox, max_characters, clock))
+	}
+
+	fn with_output(
+		output: Box<dyn Output + 'a>,
+		emojis: Emojis<'a>,
+		outbox: Outbox,
+		max_characters: usize,
+		clock: Arc<dyn Clock>,
+	) -> Self {
+		MessageProcessor {
+			output,
+			emojis,
+			limit_for_status: RateLimit::new(config::get().status_rate_limit(), Arc::clone(&clock)),
+			limit_for_ff: RateLimit::new(config::get().follow_rate_limit(), Arc::clone(&clock)),
+			max_characters,
+			clock,
+			outbox,
+			queue: VecDeque::new(),
+			queued_letters: HashSet::new(),
+		}
 	}
 
 	pub fn process(&mut self, msg: Message) -> Result<()> {
 		match msg {
 			Message::Status{
-				text, visibility, mention, in_reply_to_id, poll_options,
-			} => self.status(text, visibility, mention, in_reply_to_id, poll_options),
-			Message::Follow(id) => self.follow(id),
-			Message::Unfollow(id) => self.unfollow(id),
+				text, spoiler_text, language, visibility, mention, in_reply_to_id, poll_options, priority, context,
+			} => self.status(
+				text, spoiler_text, language, visibility, mention, in_reply_to_id, poll_options, priority, context,
+			)?,
+			Message::Follow(account) => self.enqueue(Job::Follow(account))?,
+			Message::Unfollow(account) => self.enqueue(Job::Unfollow(account))?,
+			Message::Reload(_) => warn!("Reloading must be handled before MessageProcessor"),
+			Message::Shutdown => warn!("Shutdown must be handled before MessageProcessor"),
 			Message::Error(text, e) => {
 				error!("Received error message: {}: {}", text, e);
-				Err(e)
+				return Err(e);
 			}
+		};
+		self.flush()
+	}
+
+	/// Queue statuses that are left in the outbox and whose next attempt time has come,
+	/// then send queued messages as far as the rate limits allow.
+	pub fn resend_pending(&mut self) -> Result<()> {
+		for letter in self.outbox.due(self.clock.now()) {
+			if self.queued_letters.contains(letter.id()) {
+				continue;
+			}
+			info!("Queue a pending status: id: {}, attempts: {}", letter.id(), letter.attempts());
+			self.enqueue(Job::Status(letter))?;
+		}
+		self.flush()
+	}
+
+	/// Send queued messages as far as the rate limits allow before exiting.
+	/// Statuses left in the queue are kept in the outbox and sent at the next start.
+	pub fn shutdown(&mut self) -> Result<()> {
+		self.flush()?;
+
+		for job in self.queue.drain(..) {
+			match job {
+				Job::Status(letter) => info!("Keep a pending status in outbox: id: {}", letter.id()),
+				Job::Follow(account) => warn!("Dropped follow on shutdown: {}", account.acct()),
+				Job::Unfollow(account) => warn!("Dropped unfollow on shutdown: {}", account.acct()),
+			};
+		}
+		self.queued_letters.clear();
+
+		Ok(())
+	}
+
+	/// Get the duration to wait for the next message before retrying queued messages.
+	pub fn idle_timeout(&mut self) -> Duration {
+		let max_idle = Duration::from_secs(MAX_IDLE_SECS);
+
+		if self.queue.is_empty() {
+			return max_idle;
 		}
+
+		let wait = if self.queue.iter().any(|job| job.is_status()) {
+			self.limit_for_status.wait_duration()
+		} else {
+			self.limit_for_ff.wait_duration()
+		};
+		std::cmp::min(wait, max_idle)
 	}
 
+	#[allow(clippy::too_many_arguments)]
 	fn status(
 		&mut self,
 		text: String,
+		spoiler_text: Option<String>,
+		language: Option<String>,
 		visibility: Visibility,
 		mention: Option<String>,
 		in_reply_to_id: Option<String>,
 		poll_options: Option<PollOptions>,
+		priority: Priority,
+		context: LogContext,
 	) -> Result<()> {
-		info!("Start posting a Status");
+		let context = context.or_new_correlation_id();
+		let _scope = logging::scope(context.clone());
+		info!("Received a status to post: priority: {:?}", priority);
+
+		let prefix = match mention {
+			Some(mention) => format!("@{} ", mention),
+			None => String::new(),
+		};
 
-		if let Err(e) = self.limit_for_status.increment() {
-			return Err(e);
+		// Mastodon counts the content warning in the length of the status.
+		let spoiler_text = spoiler_text.map(|s| self.emojis.emojify(s));
+		let limit = self.max_characters
+			.saturating_sub(status_text::count(&prefix))
+			.saturating_sub(spoiler_text.as_ref().map_or(0, status_text::count));
+		let parts = status_text::split(self.emojis.emojify(text), limit)
+			.into_iter()
+			.map(|part| prefix.clone() + &part)
+			.collect::<Vec<String>>();
+
+		if parts.len() > 1 {
+			info!("Split a long status into {} parts", parts.len());
 		}
-		info!("Rate limit status for Status: {}", self.limit_for_status);
 
-		let text = match mention {
-			Some(mention) => format!("@{} {}", mention, text),
-			None => text,
-		};
+		let letter = self.outbox.push(
+			parts,
+			spoiler_text,
+			language,
+			visibility,
+			in_reply_to_id,
+			poll_options,
+			priority,
+			context,
+		)?;
 
-		let post = statuses::post(self.conn).status(self.emojis.emojify(text));
-		let post = match in_reply_to_id {
-			Some(id) => post.in_reply_to_id(id),
-			None => post,
-		};
+		self.enqueue(Job::Status(letter))
+	}
 
-		let result = match poll_options {
-			Some(po) => post
-				.visibility(visibility)
-				.poll(po.poll_options, po.expires_in)
-				.send(),
-			None => post
-				.visibility(visibility)
-				.send(),
-		};
-		match result {
-			Ok(posted) => info!(
-				"Posting a status is complete: {}",
-				posted.content().unwrap().replace("\n", "")
-			),
-			Err(e) => error!("Failed to post status: {}", e),
-		};
+	fn enqueue(&mut self, job: Job) -> Result<()> {
+		if self.queue.len() >= QUEUE_CAPACITY {
+			// Drop the oldest job which has the lowest priority.
+			let lowest = self.queue.iter()
+				.enumerate()
+				.min_by(|(i, a), (j, b)| a.priority().cmp(&b.priority()).then(i.cmp(j)))
+				.map(|(i, job)| (i, job.priority()));
 
+			match lowest {
+				Some((index, priority)) if priority <= job.priority() => {
+					// Safe unwrapping because the index is taken from the queue.
+					let dropped = self.queue.remove(index).unwrap();
+					self.drop_job(dropped)?;
+				},
+				_ => return self.drop_job(job),
+			};
+		}
+
+		if let Job::Status(letter) = &job {
+			self.queued_letters.insert(letter.id().to_owned());
+		}
+		self.queue.push_back(job);
+
+		info!("Queue depth of messages: {}", self.queue.len());
 		Ok(())
 	}
 
-	fn follow(&mut self, account: Account) -> Result<()> {
-		info!("Start following an account: {}", account.acct());
+	fn flush(&mut self) -> Result<()> {
+		while let Some(index) = self.next_job() {
+			// Safe unwrapping because the index is taken from the queue.
+			match self.queue.remove(index).unwrap() {
+				Job::Status(letter) => {
+					self.queued_letters.remove(letter.id());
+					info!("Rate limit status for Status: {}", self.limit_for_status);
 
-		if let Err(e) = self.limit_for_ff.increment() {
-			return Err(e);
+					// The rest of the parts wait for the next tokens at the same place in the queue.
+					if let Some(letter) = self.deliver(letter)? {
+						self.queued_letters.insert(letter.id().to_owned());
+						self.queue.insert(index, Job::Status(letter));
+					}
+				},
+				Job::Follow(account) => {
+					info!("Rate limit status for follow/unfollow: {}", self.limit_for_ff);
+					self.follow(account);
+				},
+				Job::Unfollow(account) => {
+					info!("Rate limit status for follow/unfollow: {}", self.limit_for_ff);
+					self.unfollow(account);
+				},
+			};
 		}
-		info!("Rate limit status for follow/unfollow: {}", self.limit_for_ff);
 
-		match follow::post(self.conn, account.id()).send() {
-			Ok(_) => info!("Following an account is complete: {}", account.acct()),
-			Err(e) => error!("Failed to follow an account: {}, error: {}", account.acct(), e)
-		};
+		if !self.queue.is_empty() {
+			info!(
+				"Rate limit reached: queue depth: {}, delay: {} secs",
+				self.queue.len(),
+				self.idle_timeout().as_secs_f64(),
+			);
+		}
 
 		Ok(())
 	}
 
-	fn unfollow(&mut self, account: Account) -> Result<()> {
-		info!("Start unfollowing an account: {}", account.acct());
+	/// Find the job to send next, which has the highest priority and can acquire the rate limit.
+	fn next_job(&mut self) -> Option<usize> {
+		let mut indexes = (0 .. self.queue.len()).collect::<Vec<usize>>();
+		indexes.sort_by(|a, b| self.queue[*b].priority().cmp(&self.queue[*a].priority()));
 
-		if let Err(e) = self.limit_for_ff.increment() {
-			return Err(e);
+		for index in indexes {
+			let acquired = if self.queue[index].is_status() {
+				self.limit_for_status.acquire()
+			} else {
+				self.limit_for_ff.acquire()
+			};
+
+			if acquired {
+				return Some(index);
+			}
 		}
-		info!("Rate limit status for follow/unfollow: {}", self.limit_for_ff);
+		None
+	}
 
-		match unfollow::post(self.conn, account.id()).send() {
+	fn drop_job(&mut self, job: Job) -> Result<()> {
+		warn!("Queue is full, drop a message: priority: {:?}, queue depth: {}", job.priority(), self.queue.len());
+
+		match job {
+			Job::Status(letter) => {
+				warn!("Dropped status: {}", letter.text());
+				self.queued_letters.remove(letter.id());
+				self.outbox.remove(&letter)
+			},
+			Job::Follow(account) | Job::Unfollow(account) => {
+				warn!("Dropped follow/unfollow: {}", account.acct());
+				Ok(())
+			},
+		}
+	}
+
+	/// Post the next part of the letter, parts of the letter are posted as a thread of self-replies.
+	/// The letter is given back when it still has parts to post.
+	fn deliver(&mut self, mut letter: Letter) -> Result<Option<Letter>> {
+		let _scope = logging::scope(letter.context().clone());
+
+		let text = match letter.next_part() {
+			Some(text) => text.to_owned(),
+			None => {
+				self.outbox.remove(&letter)?;
+				return Ok(None);
+			},
+		};
+		info!("Start posting a Status: part: {}/{}", letter.num_posted() + 1, letter.num_parts());
+
+		let post = Post::new(
+			text,
+			letter.spoiler_text().map(|s| s.to_owned()),
+			letter.language().map(|l| l.to_owned()),
+			letter.visibility(),
+			letter.in_reply_to_id().map(|id| id.to_owned()),
+			letter.poll_options().cloned(),
+		);
+
+		match self.output.post(&post) {
+			Ok(id) => {
+				logging::update(|c| c.posted_id = Some(id.clone()));
+				info!("Posted a status: id: {}", id);
+				letter.mark_posted(id);
+			},
+			Err(e) => {
+				error!("Failed to post status: {}", e);
+				self.outbox.postpone(letter)?;
+				return Ok(None);
+			},
+		};
+
+		if letter.next_part().is_some() {
+			self.outbox.update(letter.clone())?;
+			Ok(Some(letter))
+		} else {
+			self.outbox.remove(&letter)?;
+			Ok(None)
+		}
+	}
+
+	fn follow(&mut self, account: Account) {
+		info!("Start following an account: {}", account.acct());
+
+		match self.output.follow(&account) {
+			Ok(_) => info!("Following an account is complete: {}", account.acct()),
+			Err(e) => error!("Failed to follow an account: {}, error: {}", account.acct(), e)
+		};
+	}
+
+	fn unfollow(&mut self, account: Account) {
+		info!("Start unfollowing an account: {}", account.acct());
+
+		match self.output.unfollow(&account) {
 			Ok(_) => info!("Unfollowing an account is complete: {}", account.acct()),
 			Err(e) => error!("Failed to unfollow an account: {}, error: {}", account.acct(), e),
 		}
+	}
 
-		Ok(())
+}
+
+/// Get the maximum number of characters of a status from the instance,
+/// or from the configuration when the instance doesn't tell it.
+fn max_characters(conn: &Connection) -> usize {
+	let fallback = config::get().max_characters();
+
+	match instance::get(conn).send() {
+		Ok(instance) => match instance.configuration().map(|c| c.statuses().max_characters()) {
+			Some(max) => {
+				info!("Maximum characters of a status: {}", max);
+				max as usize
+			},
+			None => {
+				info!("Instance doesn't tell the maximum characters of a status, use the configuration: {}", fallback);
+				fallback
+			},
+		},
+		Err(e) => {
+			warn!("Failed to get the instance, use the configured maximum characters of a status: {}, {}", fallback, e);
+			fallback
+		},
+	}
+}
+
+#[derive(Debug)]
+enum Job {
+	Status(Letter),
+	Follow(Account),
+	Unfollow(Account),
+}
+
+impl Job {
+	fn priority(&self) -> Priority {
+		match self {
+			Job::Status(letter) => letter.priority(),
+			Job::Follow(_) | Job::Unfollow(_) => Priority::High,
+		}
 	}
 
+	fn is_status(&self) -> bool {
+		matches!(self, Job::Status(_))
+	}
 }
 
 #[derive(Debug)]
 pub enum Message {
 	Status {
 		text: String,
+		spoiler_text: Option<String>,
+		language: Option<String>,
 		visibility: Visibility,
 		mention: Option<String>,
 		in_reply_to_id: Option<String>,
 		poll_options: Option<PollOptions>,
+		priority: Priority,
+		/// Log context of the event which the status responds to.
+		context: LogContext,
 	},
 	Follow(Account),
 	Unfollow(Account),
+	/// Reload the data files, with the request to reply to the admin if it is requested by a mention.
+	Reload(Option<ReloadRequest>),
+	/// Stop taking new messages, and exit after sending or saving the pending ones.
+	Shutdown,
 	Error(String, crate::Error),
 }
 
-#[derive(Debug, Clone)]
+/// Importance of the message, low priority messages are dropped first when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Priority {
+	/// Chit-chat such as Keema's responses.
+	Low,
+	/// Replies to questions.
+	Normal,
+	/// Announcements.
+	High,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PollOptions {
 	poll_options: Vec<String>,
 	expires_in: u64,
@@ -147,4 +450,209 @@ impl PollOptions {
 			expires_in,
 		}
 	}
+
+	pub fn poll_options(&self) -> &[String] {
+		&self.poll_options
+	}
+
+	pub fn expires_in(&self) -> u64 {
+		self.expires_in
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::RefCell;
+	use std::rc::Rc;
+	use chrono::Duration as ChronoDuration;
+	use serde_json::Value;
+	use crate::clock::FakeClock;
+	use crate::emojis;
+	use crate::outputs::Post;
+	use crate::test_support::{ clock, temp_dir };
+
+	#[test]
+	fn test_queue_capacity() {
+		let clock = clock();
+		let (mut processor, output) = processor("queue_capacity", &clock, 1);
+
+		for i in 0 .. QUEUE_CAPACITY + 5 {
+			processor.process(status(&i.to_string(), Priority::Normal)).unwrap();
+		}
+
+		assert_eq!(output.texts(), vec!["0"]);
+		assert_eq!(processor.queue.len(), QUEUE_CAPACITY);
+		assert_eq!(processor.outbox.len(), QUEUE_CAPACITY);
+		// The oldest ones are dropped.
+		assert_eq!(queued_texts(&processor).first().map(|t| t.as_str()), Some("5"));
+		assert_eq!(queued_texts(&processor).last().map(|t| t.as_str()), Some("54"));
+	}
+
+	#[test]
+	fn test_drop_lowest_priority_first() {
+		let clock = clock();
+		let (mut processor, _output) = processor("drop_lowest_priority_first", &clock, 1);
+		drain(&mut processor);
+
+		processor.process(status("high", Priority::High)).unwrap();
+		processor.process(status("low", Priority::Low)).unwrap();
+		for i in 2 .. QUEUE_CAPACITY {
+			processor.process(status(&i.to_string(), Priority::Normal)).unwrap();
+		}
+		assert_eq!(processor.queue.len(), QUEUE_CAPACITY);
+
+		processor.process(status("normal", Priority::Normal)).unwrap();
+		let texts = queued_texts(&processor);
+		assert!(!texts.contains(&"low".to_owned()));
+		assert!(texts.contains(&"high".to_owned()));
+		assert!(texts.contains(&"2".to_owned()));
+		assert!(texts.contains(&"normal".to_owned()));
+
+		processor.process(status("newer low", Priority::Low)).unwrap();
+		assert!(!queued_texts(&processor).contains(&"newer low".to_owned()));
+		assert_eq!(processor.queue.len(), QUEUE_CAPACITY);
+		assert_eq!(processor.outbox.len(), QUEUE_CAPACITY);
+	}
+
+	#[test]
+	fn test_send_higher_priority_first() {
+		let clock = clock();
+		let (mut processor, output) = processor("send_higher_priority_first", &clock, 3);
+		drain(&mut processor);
+
+		processor.process(status("low", Priority::Low)).unwrap();
+		processor.process(status("normal", Priority::Normal)).unwrap();
+		processor.process(status("high", Priority::High)).unwrap();
+		assert!(output.texts().is_empty());
+
+		clock.advance(ChronoDuration::minutes(2));
+		processor.flush().unwrap();
+		assert_eq!(output.texts(), vec!["high", "normal", "low"]);
+	}
+
+	#[test]
+	fn test_delay_when_rate_limited() {
+		let clock = clock();
+		let (mut processor, output) = processor("delay_when_rate_limited", &clock, 1);
+		drain(&mut processor);
+
+		processor.process(status("どうぞ！", Priority::Normal)).unwrap();
+		assert!(output.texts().is_empty());
+		assert_eq!(processor.queue.len(), 1);
+		assert_eq!(processor.idle_timeout(), Duration::from_secs(MAX_IDLE_SECS));
+
+		clock.advance(ChronoDuration::seconds(55));
+		processor.flush().unwrap();
+		assert!(output.texts().is_empty());
+		assert!(processor.idle_timeout() > Duration::from_secs(4));
+		assert!(processor.idle_timeout() <= Duration::from_secs(5));
+
+		clock.advance(ChronoDuration::seconds(10));
+		processor.flush().unwrap();
+		assert_eq!(output.texts(), vec!["どうぞ！"]);
+		assert!(processor.queue.is_empty());
+		assert!(processor.outbox.is_empty());
+	}
+
+	#[test]
+	fn test_post_parts_with_own_tokens() {
+		let clock = clock();
+		let (mut processor, output) = processor("post_parts_with_own_tokens", &clock, 1);
+		let text = "あ".repeat(300) + "\n\n" + &"い".repeat(300);
+
+		processor.process(Message::Status {
+			poll_options: Some(PollOptions::new(vec!["はい".to_owned(), "いいえ".to_owned()], 300)),
+			..status(&text, Priority::Normal)
+		}).unwrap();
+		assert_eq!(output.texts(), vec!["あ".repeat(300)]);
+		assert_eq!(processor.queue.len(), 1);
+		assert_eq!(processor.outbox[0].num_posted(), 1);
+
+		clock.advance(ChronoDuration::minutes(2));
+		processor.flush().unwrap();
+		assert_eq!(output.texts(), vec!["あ".repeat(300), "い".repeat(300)]);
+		assert!(processor.queue.is_empty());
+		assert!(processor.outbox.is_empty());
+
+		// The poll is attached to the last part only.
+		let posts = output.posts.borrow();
+		assert!(posts[0]["poll_options"].is_null());
+		assert_eq!(posts[1]["poll_options"]["poll_options"][0], "はい");
+		assert_eq!(posts[1]["in_reply_to_id"], "1");
+	}
+
+	/// Output which records the posted statuses.
+	#[derive(Debug, Clone, Default)]
+	struct FakeOutput {
+		posts: Rc<RefCell<Vec<Value>>>,
+	}
+
+	impl FakeOutput {
+		fn texts(&self) -> Vec<String> {
+			self.posts.borrow().iter()
+				.map(|post| post["text"].as_str().unwrap().to_owned())
+				.collect()
+		}
+	}
+
+	impl Output for FakeOutput {
+		fn post(&mut self, post: &Post) -> Result<String> {
+			let mut posts = self.posts.borrow_mut();
+			posts.push(serde_json::to_value(post).unwrap());
+			Ok(posts.len().to_string())
+		}
+
+		fn follow(&mut self, _account: &Account) -> Result<()> {
+			Ok(())
+		}
+
+		fn unfollow(&mut self, _account: &Account) -> Result<()> {
+			Ok(())
+		}
+	}
+
+	fn processor(name: &str, clock: &FakeClock, status_rate_limit: usize) -> (MessageProcessor<'static>, FakeOutput) {
+		let clock: Arc<dyn Clock> = Arc::new(clock.clone());
+		let output = FakeOutput::default();
+
+		let mut processor = MessageProcessor::with_output(
+			Box::new(output.clone()),
+			emojis::tests::cached(),
+			Outbox::load_from(temp_dir(&format!("message_processor_{}", name)), Arc::clone(&clock)).unwrap(),
+			500,
+			Arc::clone(&clock),
+		);
+		processor.limit_for_status = RateLimit::new(status_rate_limit, clock);
+
+		(processor, output)
+	}
+
+	/// Use up the rate limit for statuses.
+	fn drain(processor: &mut MessageProcessor) {
+		while processor.limit_for_status.acquire() {}
+	}
+
+	fn status(text: &str, priority: Priority) -> Message {
+		Message::Status {
+			text: text.to_owned(),
+			spoiler_text: None,
+			language: None,
+			visibility: Visibility::Public,
+			mention: None,
+			in_reply_to_id: None,
+			poll_options: None,
+			priority,
+			context: LogContext::default(),
+		}
+	}
+
+	fn queued_texts(processor: &MessageProcessor) -> Vec<String> {
+		processor.queue.iter()
+			.filter_map(|job| match job {
+				Job::Status(letter) => Some(letter.text()),
+				_ => None,
+			})
+			.collect()
+	}
 }
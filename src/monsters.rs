@@ -1,26 +1,29 @@
 use std::collections::HashMap;
 use std::fs::{ File, self };
 use std::io::BufReader;
+use std::sync::Arc;
 use regex::Regex;
 use serde::Deserialize;
 use crate::{
 	Error,
 	Result,
+	config,
 	resistances::Resistances,
 	utils::transform_string_to_regex,
+	validation::Validator,
 };
-use super::contents::{ Responder, ResponseCriteria };
+use super::contents::{ Responder, Response, ResponseCriteria, Template, Text };
 
-const DATA: &str = "drakeema-data/contents/monsters.json";
-const DATA_DIR: &str = "drakeema-data/monsters";
+const DATA: &str = "contents/monsters.json";
+const DATA_DIR: &str = "monsters";
 
 #[derive(Debug, Clone)]
 pub struct Monsters {
-	information: String,
-	information_without_resistance: String,
+	information: Template,
+	information_without_resistance: Template,
 	area_names: HashMap<String, Vec<String>>,
 	ignore_categories: Vec<String>,
-	inner: HashMap<String, Monster>,
+	inner: HashMap<String, Arc<Monster>>,
 }
 
 const EXTENSION: &str = "json";
@@ -30,13 +33,13 @@ impl Monsters {
 		info!("Initialize Monsters");
 
 		let monsters_json: MonstersJson = serde_json::from_reader(
-			BufReader::new(File::open(DATA)?)
+			BufReader::new(File::open(config::data_path(DATA))?)
 		)
 		.map_err(|e| Error::UnparseableJson(DATA.to_owned(), e))?;
 
 		let mut inner = HashMap::new();
 
-		let files = fs::read_dir(DATA_DIR)?
+		let files = fs::read_dir(config::data_path(DATA_DIR))?
 			.filter_map(|dir_entry| {
     			let dir_entry = dir_entry.ok()?;
     			if dir_entry.file_type().ok()?.is_file() &&
@@ -55,7 +58,7 @@ impl Monsters {
 			)
 			.map_err(|e| Error::UnparseableJson(file.to_string_lossy().to_string(), e))?;    
 
-    		inner.insert(m.id().to_owned(), m);
+    		inner.insert(m.id().to_owned(), Arc::new(m));
     	}
     
     	Ok(Monsters {
@@ -68,8 +71,62 @@ impl Monsters {
 	}
 }
 
+/// Validate the monster files, and return the monsters which could be loaded.
+pub fn validate(validator: &mut Validator) -> HashMap<String, Monster> {
+	let mut monsters = HashMap::new();
+
+	let monsters_json: Option<MonstersJson> = validator.parse(DATA);
+	if let Some(json) = monsters_json.as_ref() {
+		validator.check_template(DATA, "information", &json.information, &["__NAME__", "__RESISTANCES__"]);
+		validator.check_template(DATA, "information_without_resistance", &json.information_without_resistance, &["__NAME__"]);
+	}
+
+	let entries = match fs::read_dir(config::data_path(DATA_DIR)) {
+		Ok(entries) => entries,
+		Err(e) => {
+			validator.push(DATA_DIR, "-", format!("cannot read the directory: {}", e));
+			return monsters;
+		},
+	};
+
+	for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+		if !path.is_file() || path.extension().map_or(true, |ext| ext != EXTENSION) {
+			continue;
+		}
+
+		// Name the file relative to the data root, as the validator resolves it.
+		let file = match path.file_name() {
+			Some(name) => format!("{}/{}", DATA_DIR, name.to_string_lossy()),
+			None => continue,
+		};
+		let monster: Monster = match validator.parse(&file) {
+			Some(monster) => monster,
+			None => continue,
+		};
+
+		if let Some(json) = monsters_json.as_ref() {
+			let num_areas = monster.resistances().len();
+			let num_names = json.area_names.get(monster.category()).map_or(0, |names| names.len());
+
+			if num_areas > 1 && num_names < num_areas {
+				validator.push(&file, "resistances", format!(
+					"resistances for {} areas are given but area_names for category {} has {} names",
+					num_areas, monster.category(), num_names
+				));
+			}
+		}
+
+		if monsters.contains_key(monster.id()) {
+			validator.push(&file, "id", format!("duplicated monster ID: {}", monster.id()));
+		}
+		monsters.insert(monster.id().to_owned(), monster);
+	}
+
+	monsters
+}
+
 impl Responder for Monsters {
-	fn respond(&self, criteria: &ResponseCriteria) -> Option<String> {
+	fn respond(&self, criteria: &ResponseCriteria) -> Option<Response> {
 		trace!("Start Responder about monsters");
 		let reaction = self.iter()
 			.filter(|(_, m)| {
@@ -79,29 +136,33 @@ impl Responder for Monsters {
 			.map(|(_, m)| {
 				let resistances = &m.resistances().display(self.area_names.get(m.category()));
 				if resistances.is_empty() {
-					self.information_without_resistance
-						.replace("__NAME__", m.official_name())
+					self.information_without_resistance.render(&[
+						("__NAME__", m.official_name()),
+					])
 				} else {
-					self.information
-						.replace("__NAME__", m.official_name())
-						.replace("__RESISTANCES__", resistances)
+					self.information.render(&[
+						("__NAME__", m.official_name()),
+						("__RESISTANCES__", resistances),
+					])
 				}
 			})
-			.collect::<Vec<String>>()
-			.join("\n");
+			.collect::<Vec<Text>>();
 		
-		if reaction.is_empty() {
-			trace!("Nothing reaction about monsters: {:?}", criteria);
-			None
-		} else {
-			info!("Found reaction about monsters: {:?}, {}", criteria, reaction);
-			Some(reaction)
+		match Text::join(reaction, "\n") {
+			Some(reaction) => {
+				info!("Found reaction about monsters: {:?}, {}", criteria, reaction.text());
+				Some(Response::from(reaction))
+			},
+			None => {
+				trace!("Nothing reaction about monsters: {:?}", criteria);
+				None
+			},
 		}
 	}
 }
 
 impl std::ops::Deref for Monsters {
-	type Target = HashMap<String, Monster>;
+	type Target = HashMap<String, Arc<Monster>>;
 	fn deref(&self) -> &Self::Target {
 		&self.inner
 	}
@@ -156,8 +217,8 @@ impl Monster {
 
 #[derive(Debug, Clone, Deserialize)]
 struct MonstersJson {
-	information: String,
-	information_without_resistance: String,
+	information: Template,
+	information_without_resistance: Template,
 	area_names: HashMap<String, Vec<String>>,
 	ignore_categories: Vec<String>,
 }
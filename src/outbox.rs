@@ -0,0 +1,419 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use chrono::{ DateTime, Duration, Utc };
+use chrono_tz::Tz;
+use mastors::entities::Visibility;
+use serde::{ Deserialize, Serialize };
+use crate::{
+	Error,
+	Result,
+	backoff::Backoff,
+	clock::Clock,
+	logging::LogContext,
+	message_processor::{ PollOptions, Priority },
+	tmp_file,
+};
+
+const OUTBOX_DIR: &str = "outbox";
+const DRY_RUN_OUTBOX_DIR: &str = "outbox-dry-run";
+const EXTENSION: &str = "json";
+const RETRY_BASE_SECS: u64 = 30;
+const RETRY_MAX_SECS: u64 = 3600;
+const MAX_ATTEMPTS: u32 = 10;
+
+/// Statuses waiting to be posted, saved in the temporary directory until posting is complete.
+#[derive(Debug)]
+pub struct Outbox {
+	clock: Arc<dyn Clock>,
+	dir: PathBuf,
+	backoff: Backoff,
+	sequence: u64,
+	inner: Vec<Letter>,
+}
+
+impl Outbox {
+	pub fn load(clock: Arc<dyn Clock>) -> Result<Self> {
+		Self::load_from(tmp_file::tmp_dir(OUTBOX_DIR)?, clock)
+	}
+
+	/// Load the outbox separated from the real one so that dry runs don't consume pending statuses.
+	pub fn load_dry_run(clock: Arc<dyn Clock>) -> Result<Self> {
+		Self::load_from(tmp_file::tmp_dir(DRY_RUN_OUTBOX_DIR)?, clock)
+	}
+
+	/// Load the outbox from the directory which already exists.
+	pub(crate) fn load_from(dir: PathBuf, clock: Arc<dyn Clock>) -> Result<Self> {
+		info!("Initialize Outbox: {:?}", dir);
+
+		let entries = fs::read_dir(&dir)
+			.map_err(|e| Error::LoadTmpData(dir.to_string_lossy().to_string(), e))?;
+
+		let mut inner: Vec<Letter> = Vec::new();
+		for entry in entries {
+			let path = match entry {
+				Ok(entry) => entry.path(),
+				Err(e) => {
+					error!("Failed to read an entry of outbox: {}", e);
+					continue;
+				},
+			};
+
+			if path.extension().map_or(true, |ext| ext != EXTENSION) {
+				continue;
+			}
+
+			match fs::read(&path) {
+				Ok(bytes) => match serde_json::from_slice::<Letter>(&bytes) {
+					Ok(letter) => inner.push(letter),
+					Err(e) => error!("Skip unparseable letter in outbox: {:?}, {}", path, e),
+				},
+				Err(e) => error!("Failed to read a letter in outbox: {:?}, {}", path, e),
+			}
+		}
+		inner.sort_by(|a, b| a.id.cmp(&b.id));
+
+		info!("Pending statuses in outbox: {}", inner.len());
+
+		Ok(Outbox {
+			clock,
+			dir,
+			backoff: Backoff::new(
+				StdDuration::from_secs(RETRY_BASE_SECS),
+				StdDuration::from_secs(RETRY_MAX_SECS),
+			),
+			sequence: 0,
+			inner,
+		})
+	}
+
+	/// Save a new status to the outbox before posting it.
+	/// A status that is too long to post at once is given as multiple parts.
+	#[allow(clippy::too_many_arguments)]
+	pub fn push(
+		&mut self,
+		parts: Vec<String>,
+		spoiler_text: Option<String>,
+		language: Option<String>,
+		visibility: Visibility,
+		in_reply_to_id: Option<String>,
+		poll_options: Option<PollOptions>,
+		priority: Priority,
+		context: LogContext,
+	) -> Result<Letter> {
+		let now = self.clock.now();
+		self.sequence += 1;
+
+		let letter = Letter {
+			id: format!("{}-{:06}", now.format("%Y%m%d%H%M%S%3f"), self.sequence),
+			parts,
+			spoiler_text,
+			language,
+			visibility,
+			in_reply_to_id,
+			poll_options,
+			priority,
+			context,
+			posted_ids: Vec::new(),
+			attempts: 0,
+			next_attempt_at: now.with_timezone(&Utc),
+		};
+
+		self.save(&letter)?;
+		self.inner.push(letter.clone());
+
+		Ok(letter)
+	}
+
+	/// Remove the letter that has been posted.
+	pub fn remove(&mut self, letter: &Letter) -> Result<()> {
+		let path = self.path(letter);
+		debug!("Remove a letter from outbox: {:?}", path);
+
+		if path.exists() {
+			fs::remove_file(&path)
+				.map_err(|e| Error::SaveTmpData(path.to_string_lossy().to_string(), e))?;
+		}
+		self.inner.retain(|l| l.id != letter.id);
+
+		Ok(())
+	}
+
+	/// Schedule the next attempt of the letter that failed to post.
+	/// The letter is discarded when it reaches the maximum number of attempts.
+	pub fn postpone(&mut self, mut letter: Letter) -> Result<()> {
+		letter.attempts += 1;
+
+		if letter.attempts >= MAX_ATTEMPTS {
+			error!("Give up posting a status: attempts: {}, text: {}", letter.attempts, letter.text());
+			return self.remove(&letter);
+		}
+
+		let delay = self.backoff.delay(letter.attempts - 1);
+		letter.next_attempt_at = (self.clock.now() + Duration::seconds(delay.as_secs() as i64)).with_timezone(&Utc);
+
+		info!(
+			"Posting a status will be retried at {}: attempts: {}, id: {}",
+			letter.next_attempt_at, letter.attempts, letter.id
+		);
+
+		self.update(letter)
+	}
+
+	/// Save the progress of the letter which is partially posted.
+	pub fn update(&mut self, letter: Letter) -> Result<()> {
+		self.save(&letter)?;
+		match self.inner.iter_mut().find(|l| l.id == letter.id) {
+			Some(l) => *l = letter,
+			None => self.inner.push(letter),
+		};
+
+		Ok(())
+	}
+
+	/// Get letters to be posted at the specified time.
+	pub fn due(&self, at: DateTime<Tz>) -> Vec<Letter> {
+		self.inner.iter()
+			.filter(|l| l.next_attempt_at <= at.with_timezone(&Utc))
+			.cloned()
+			.collect()
+	}
+
+	fn save(&self, letter: &Letter) -> Result<()> {
+		let path = self.path(letter);
+		debug!("Save a letter to outbox: {:?}", path);
+
+		let json = serde_json::to_string(letter)
+			.map_err(|e| Error::UnserializableJson(letter.id.to_owned(), e))?;
+
+		tmp_file::replace_file(&path, json)
+			.map_err(|e| Error::SaveTmpData(path.to_string_lossy().to_string(), e))
+	}
+
+	fn path(&self, letter: &Letter) -> PathBuf {
+		self.dir.join(format!("{}.{}", letter.id, EXTENSION))
+	}
+}
+
+impl std::ops::Deref for Outbox {
+	type Target = Vec<Letter>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Letter {
+	id: String,
+	parts: Vec<String>,
+	#[serde(default)]
+	spoiler_text: Option<String>,
+	#[serde(default)]
+	language: Option<String>,
+	visibility: Visibility,
+	in_reply_to_id: Option<String>,
+	poll_options: Option<PollOptions>,
+	priority: Priority,
+	#[serde(default)]
+	context: LogContext,
+	posted_ids: Vec<String>,
+	attempts: u32,
+	next_attempt_at: DateTime<Utc>,
+}
+
+impl Letter {
+	pub fn id(&self) -> &str {
+		&self.id
+	}
+
+	/// Get the whole text to post, already emojified and mentioned.
+	pub fn text(&self) -> String {
+		self.parts.join("\n\n")
+	}
+
+	/// Get the part to post next, or `None` if all parts have been posted.
+	pub fn next_part(&self) -> Option<&str> {
+		self.parts.get(self.posted_ids.len()).map(|p| p.as_str())
+	}
+
+	pub fn num_parts(&self) -> usize {
+		self.parts.len()
+	}
+
+	pub fn num_posted(&self) -> usize {
+		self.posted_ids.len()
+	}
+
+	/// Record the ID of the part that has been posted.
+	pub fn mark_posted(&mut self, id: impl Into<String>) {
+		self.posted_ids.push(id.into());
+	}
+
+	/// Get the content warning which is attached to every part.
+	pub fn spoiler_text(&self) -> Option<&str> {
+		self.spoiler_text.as_deref()
+	}
+
+	pub fn language(&self) -> Option<&str> {
+		self.language.as_deref()
+	}
+
+	pub fn visibility(&self) -> Visibility {
+		self.visibility
+	}
+
+	/// Get the status ID to reply to with the next part,
+	/// the following parts are posted as replies to the previous part.
+	pub fn in_reply_to_id(&self) -> Option<&str> {
+		match self.posted_ids.last() {
+			Some(id) => Some(id.as_str()),
+			None => self.in_reply_to_id.as_deref(),
+		}
+	}
+
+	/// Get the poll options which are attached to the last part only,
+	/// so that the poll comes after the whole question.
+	pub fn poll_options(&self) -> Option<&PollOptions> {
+		if self.posted_ids.len() + 1 == self.parts.len() {
+			self.poll_options.as_ref()
+		} else {
+			None
+		}
+	}
+
+	pub fn priority(&self) -> Priority {
+		self.priority
+	}
+
+	/// Get the log context of the message which the letter is made from.
+	pub fn context(&self) -> &LogContext {
+		&self.context
+	}
+
+	pub fn attempts(&self) -> u32 {
+		self.attempts
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_support::{ clock, temp_dir };
+
+	#[test]
+	fn test_push_and_remove() {
+		let dir = temp_dir("outbox_push_and_remove");
+		let mut outbox = Outbox::load_from(dir.clone(), Arc::new(clock())).unwrap();
+
+		let letter = push(&mut outbox, "どうぞ！");
+		let path = dir.join("20200906060000000-000001.json");
+		assert_eq!(letter.id(), "20200906060000000-000001");
+		assert!(path.exists());
+		assert_eq!(outbox.len(), 1);
+
+		outbox.remove(&letter).unwrap();
+		assert!(!path.exists());
+		assert!(outbox.is_empty());
+	}
+
+	#[test]
+	fn test_postpone() {
+		let dir = temp_dir("outbox_postpone");
+		let clock = clock();
+		let mut outbox = Outbox::load_from(dir.clone(), Arc::new(clock.clone())).unwrap();
+		let letter = push(&mut outbox, "どうぞ！");
+		let path = dir.join(format!("{}.json", letter.id()));
+
+		outbox.postpone(letter).unwrap();
+		assert_eq!(outbox[0].attempts(), 1);
+		assert!(outbox.due(clock.now() + Duration::seconds(29)).is_empty());
+		assert_eq!(outbox.due(clock.now() + Duration::seconds(30)).len(), 1);
+
+		outbox.postpone(outbox[0].clone()).unwrap();
+		assert_eq!(outbox[0].attempts(), 2);
+		assert!(outbox.due(clock.now() + Duration::seconds(59)).is_empty());
+		assert_eq!(outbox.due(clock.now() + Duration::seconds(60)).len(), 1);
+
+		for _ in 2 .. MAX_ATTEMPTS - 1 {
+			outbox.postpone(outbox[0].clone()).unwrap();
+		}
+		assert_eq!(outbox[0].attempts(), MAX_ATTEMPTS - 1);
+		assert!(path.exists());
+
+		outbox.postpone(outbox[0].clone()).unwrap();
+		assert!(outbox.is_empty());
+		assert!(!path.exists());
+	}
+
+	#[test]
+	fn test_due() {
+		let dir = temp_dir("outbox_due");
+		let clock = clock();
+		let mut outbox = Outbox::load_from(dir, Arc::new(clock.clone())).unwrap();
+
+		let failed = push(&mut outbox, "どうぞ！");
+		let pending = push(&mut outbox, "ダメです！");
+		outbox.postpone(failed.clone()).unwrap();
+
+		let due = outbox.due(clock.now());
+		assert_eq!(due.len(), 1);
+		assert_eq!(due[0].id(), pending.id());
+
+		clock.advance(Duration::seconds(30));
+		let due = outbox.due(clock.now());
+		assert_eq!(due.iter().map(|l| l.id()).collect::<Vec<&str>>(), vec![failed.id(), pending.id()]);
+	}
+
+	#[test]
+	fn test_load_pending_letters() {
+		let dir = temp_dir("outbox_load_pending_letters");
+		let clock = clock();
+
+		let (partial, failed) = {
+			let mut outbox = Outbox::load_from(dir.clone(), Arc::new(clock.clone())).unwrap();
+
+			let mut partial = outbox.push(
+				vec!["一つ目".to_owned(), "二つ目".to_owned()],
+				None,
+				None,
+				Visibility::Public,
+				Some("100".to_owned()),
+				None,
+				Priority::High,
+				LogContext::default(),
+			).unwrap();
+			partial.mark_posted("101");
+			outbox.update(partial.clone()).unwrap();
+
+			let failed = push(&mut outbox, "どうぞ！");
+			outbox.postpone(failed.clone()).unwrap();
+			(partial, failed)
+		};
+		fs::write(dir.join("broken.json"), "{").unwrap();
+		fs::write(dir.join("crashed.json.partial"), "{").unwrap();
+
+		let outbox = Outbox::load_from(dir, Arc::new(clock.clone())).unwrap();
+		assert_eq!(outbox.iter().map(|l| l.id()).collect::<Vec<&str>>(), vec![partial.id(), failed.id()]);
+
+		assert_eq!(outbox[0].next_part(), Some("二つ目"));
+		assert_eq!(outbox[0].in_reply_to_id(), Some("101"));
+		assert_eq!(outbox[0].priority(), Priority::High);
+		assert_eq!(outbox[1].attempts(), 1);
+		assert_eq!(outbox.due(clock.now()).len(), 1);
+	}
+
+	fn push(outbox: &mut Outbox, text: &str) -> Letter {
+		outbox.push(
+			vec![text.to_owned()],
+			None,
+			None,
+			Visibility::Public,
+			None,
+			None,
+			Priority::Normal,
+			LogContext::default(),
+		).unwrap()
+	}
+}
@@ -0,0 +1,64 @@
+use mastors::prelude::*;
+use mastors::api::v1::{
+	statuses,
+	accounts::id::{ follow, unfollow },
+};
+use crate::Result;
+use super::{ Output, Post };
+
+/// Output to post statuses to the Mastodon server.
+pub struct MastodonOutput<'a> {
+	conn: &'a Connection,
+}
+
+impl<'a> MastodonOutput<'a> {
+	pub fn new(conn: &'a Connection) -> Self {
+		MastodonOutput {
+			conn,
+		}
+	}
+}
+
+impl<'a> Output for MastodonOutput<'a> {
+	fn post(&mut self, post: &Post) -> Result<String> {
+		let request = statuses::post(self.conn)
+			.status(post.text.as_str())
+			.visibility(post.visibility);
+		let request = match post.in_reply_to_id.as_ref() {
+			Some(id) => request.in_reply_to_id(id.as_str()),
+			None => request,
+		};
+		let request = match post.spoiler_text.as_ref() {
+			Some(spoiler_text) => request.spoiler_text(spoiler_text.as_str()),
+			None => request,
+		};
+		let request = match post.language.as_ref() {
+			Some(language) => request.language(language.as_str()),
+			None => request,
+		};
+
+		let posted = match post.poll_options.as_ref() {
+			Some(po) => request
+				.poll(po.poll_options().to_vec(), po.expires_in())
+				.send()?,
+			None => request
+				.send()?,
+		};
+
+		info!(
+			"Posting a status is complete: {}",
+			posted.content().unwrap_or_default().replace("\n", "")
+		);
+		Ok(posted.id().to_owned())
+	}
+
+	fn follow(&mut self, account: &Account) -> Result<()> {
+		follow::post(self.conn, account.id()).send()?;
+		Ok(())
+	}
+
+	fn unfollow(&mut self, account: &Account) -> Result<()> {
+		unfollow::post(self.conn, account.id()).send()?;
+		Ok(())
+	}
+}
@@ -0,0 +1,52 @@
+mod mastodon;
+mod stdout;
+
+pub use mastodon::MastodonOutput;
+pub use stdout::StdoutOutput;
+
+use mastors::entities::{ Account, Visibility };
+use serde::Serialize;
+use crate::{
+	Result,
+	message_processor::PollOptions,
+};
+
+/// Destination of the messages processed by `MessageProcessor`.
+pub trait Output {
+	/// Post a status and return the ID of the posted status.
+	fn post(&mut self, post: &Post) -> Result<String>;
+	fn follow(&mut self, account: &Account) -> Result<()>;
+	fn unfollow(&mut self, account: &Account) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Post {
+	text: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	spoiler_text: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	language: Option<String>,
+	visibility: Visibility,
+	in_reply_to_id: Option<String>,
+	poll_options: Option<PollOptions>,
+}
+
+impl Post {
+	pub fn new(
+		text: impl Into<String>,
+		spoiler_text: Option<String>,
+		language: Option<String>,
+		visibility: Visibility,
+		in_reply_to_id: Option<String>,
+		poll_options: Option<PollOptions>,
+	) -> Self {
+		Post {
+			text: text.into(),
+			spoiler_text,
+			language,
+			visibility,
+			in_reply_to_id,
+			poll_options,
+		}
+	}
+}
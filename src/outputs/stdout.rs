@@ -0,0 +1,82 @@
+use std::io::{ self, Write };
+use mastors::entities::Account;
+use serde::Serialize;
+use crate::{
+	Error,
+	Result,
+};
+use super::{ Output, Post };
+
+/// Output to write messages to the standard output as JSON lines instead of sending them.
+#[derive(Debug, Default)]
+pub struct StdoutOutput {
+	sequence: u64,
+}
+
+impl StdoutOutput {
+	pub fn new() -> Self {
+		StdoutOutput::default()
+	}
+
+	fn write(&self, record: &Record) -> Result<()> {
+		let json = serde_json::to_string(record)
+			.map_err(|e| Error::UnserializableJson(record.id.to_owned(), e))?;
+
+		let stdout = io::stdout();
+		let mut handle = stdout.lock();
+		writeln!(handle, "{}", json)?;
+		handle.flush()?;
+
+		Ok(())
+	}
+
+	fn next_id(&mut self) -> String {
+		self.sequence += 1;
+		format!("dry-run-{}", self.sequence)
+	}
+}
+
+impl Output for StdoutOutput {
+	fn post(&mut self, post: &Post) -> Result<String> {
+		let id = self.next_id();
+		self.write(&Record {
+			id: id.clone(),
+			kind: "status",
+			acct: None,
+			status: Some(post),
+		})?;
+
+		Ok(id)
+	}
+
+	fn follow(&mut self, account: &Account) -> Result<()> {
+		let id = self.next_id();
+		self.write(&Record {
+			id,
+			kind: "follow",
+			acct: Some(account.acct()),
+			status: None,
+		})
+	}
+
+	fn unfollow(&mut self, account: &Account) -> Result<()> {
+		let id = self.next_id();
+		self.write(&Record {
+			id,
+			kind: "unfollow",
+			acct: Some(account.acct()),
+			status: None,
+		})
+	}
+}
+
+#[derive(Debug, Serialize)]
+struct Record<'a> {
+	id: String,
+	#[serde(rename = "type")]
+	kind: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	acct: Option<&'a str>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	status: Option<&'a Post>,
+}
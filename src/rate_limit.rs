@@ -1,44 +1,67 @@
-use chrono::{ DateTime, Duration, Local };
-use crate::{
-	Error,
-	Result,
-};
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use crate::clock::Clock;
 
-const RESET_INTERVAL: i64 = 1;
+const REFILL_INTERVAL_SECS: f64 = 60.0;
 
-#[derive(Debug, Clone)]
+/// Token bucket which allows `limit` requests per minute.
+#[derive(Clone)]
 pub struct RateLimit {
-	from: DateTime<Local>,
-	limit: usize,
-	count: usize,
+	clock: Arc<dyn Clock>,
+	capacity: f64,
+	tokens: f64,
+	refilled_at: DateTime<Tz>,
 }
 
 impl RateLimit {
-	pub fn new(limit: usize) -> Self {
+	pub fn new(limit: usize, clock: Arc<dyn Clock>) -> Self {
+		let capacity = std::cmp::max(limit, 1) as f64;
+
 		RateLimit {
-			from: Local::now(),
-			limit,
-			count: 0,
+			capacity,
+			tokens: capacity,
+			refilled_at: clock.now(),
+			clock,
+		}
+	}
+
+	/// Take a token if the bucket has one.
+	pub fn acquire(&mut self) -> bool {
+		self.refill(self.clock.now());
+		trace!("Acquire a token: {}", self);
+
+		if self.tokens >= 1.0 {
+			self.tokens -= 1.0;
+			true
+		} else {
+			false
 		}
 	}
 
-	pub fn increment(&mut self) -> Result<usize> {
-		trace!("from: {}, now: {}, count: {}", self.from, Local::now(), self.count);
+	/// Get the duration until the bucket has a token.
+	pub fn wait_duration(&mut self) -> StdDuration {
+		self.refill(self.clock.now());
 
-		let count = if Local::now() - self.from > Duration::minutes(RESET_INTERVAL) {
-			self.from = Local::now();
-			self.count = 1;
-			self.count
+		if self.tokens >= 1.0 {
+			StdDuration::from_secs(0)
 		} else {
-			self.count += 1;
+			StdDuration::from_secs_f64((1.0 - self.tokens) / self.rate())
+		}
+	}
+
+	fn rate(&self) -> f64 {
+		self.capacity / REFILL_INTERVAL_SECS
+	}
+
+	fn refill(&mut self, now: DateTime<Tz>) {
+		let elapsed_secs = (now - self.refilled_at).num_milliseconds() as f64 / 1000.0;
 
-			if self.count > self.limit {
-				return Err(Error::ExceedRateLimit(self.limit))
-			} else {
-				self.count
-			}
-		};
-		Ok(count)
+		if elapsed_secs > 0.0 {
+			self.tokens = (self.tokens + elapsed_secs * self.rate()).min(self.capacity);
+			self.refilled_at = now;
+		}
 	}
 }
 
@@ -46,6 +69,44 @@ use std::fmt;
 
 impl fmt::Display for RateLimit {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "from: {}, limit: {}, count: {}", self.from, self.limit, self.count)
+		write!(f, "refilled_at: {}, capacity: {}, tokens: {:.2}", self.refilled_at, self.capacity, self.tokens)
+	}
+}
+
+impl fmt::Debug for RateLimit {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "RateLimit {{ {} }}", self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use chrono::{ Duration, TimeZone };
+	use chrono_tz::Asia::Tokyo;
+	use crate::clock::FakeClock;
+
+	#[test]
+	fn test_acquire_and_refill() {
+		let clock = FakeClock::new(Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 0).unwrap());
+		let mut limit = RateLimit::new(2, Arc::new(clock.clone()));
+
+		assert!(limit.acquire());
+		assert!(limit.acquire());
+		assert!(!limit.acquire());
+		assert_eq!(limit.wait_duration(), StdDuration::from_secs(30));
+
+		clock.advance(Duration::seconds(15));
+		assert!(!limit.acquire());
+		assert_eq!(limit.wait_duration(), StdDuration::from_secs(15));
+
+		clock.advance(Duration::seconds(15));
+		assert!(limit.acquire());
+		assert!(!limit.acquire());
+
+		clock.advance(Duration::minutes(10));
+		assert!(limit.acquire());
+		assert!(limit.acquire());
+		assert!(!limit.acquire());
 	}
 }
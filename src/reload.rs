@@ -0,0 +1,237 @@
+use std::sync::{ Arc, Condvar, Mutex, RwLock };
+use std::time::Duration;
+use mastors::entities::Visibility;
+use crate::{
+	Error,
+	Message,
+	Result,
+	Monsters,
+	clock::Clock,
+	features::announcement::Contents,
+	features::response::{
+		NotificationProcessor,
+		StatusProcessor,
+	},
+	logging::LogContext,
+	message_processor::Priority,
+	validation,
+};
+
+/// Value shared between threads, which can be replaced as a whole while it is used.
+/// Readers keep using the value they got until they get it again.
+#[derive(Debug)]
+pub struct Swappable<T> {
+	inner: RwLock<Arc<T>>,
+	version: Mutex<u64>,
+	changed: Condvar,
+}
+
+impl<T> Swappable<T> {
+	pub fn new(value: T) -> Self {
+		Swappable {
+			inner: RwLock::new(Arc::new(value)),
+			version: Mutex::new(0),
+			changed: Condvar::new(),
+		}
+	}
+
+	pub fn load(&self) -> Arc<T> {
+		// The lock is never held while the value is used, so a poisoned lock still has a sound value.
+		let inner = self.inner.read().unwrap_or_else(|e| e.into_inner());
+		Arc::clone(&inner)
+	}
+
+	pub fn store(&self, value: T) {
+		{
+			let mut inner = self.inner.write().unwrap_or_else(|e| e.into_inner());
+			*inner = Arc::new(value);
+		}
+
+		let mut version = self.version.lock().unwrap_or_else(|e| e.into_inner());
+		*version += 1;
+		self.changed.notify_all();
+	}
+
+	/// Get the number of times the value has been replaced.
+	pub fn version(&self) -> u64 {
+		*self.version.lock().unwrap_or_else(|e| e.into_inner())
+	}
+
+	/// Wait until the value is replaced after the version or the timeout passes,
+	/// and return `true` if the value has been replaced.
+	pub fn wait_for_change(&self, version: u64, timeout: Duration) -> bool {
+		let guard = self.version.lock().unwrap_or_else(|e| e.into_inner());
+		let (guard, _) = self.changed.wait_timeout_while(guard, timeout, |v| *v == version)
+			.unwrap_or_else(|e| e.into_inner());
+		*guard != version
+	}
+}
+
+/// Reply to the admin who requested reloading by a mention.
+#[derive(Debug, Clone)]
+pub struct ReloadRequest {
+	mention: String,
+	in_reply_to_id: String,
+	visibility: Visibility,
+	reloaded_message: String,
+	failed_message: String,
+	context: LogContext,
+}
+
+impl ReloadRequest {
+	pub fn new(
+		mention: impl Into<String>,
+		in_reply_to_id: impl Into<String>,
+		visibility: Visibility,
+		reloaded_message: impl Into<String>,
+		failed_message: impl Into<String>,
+	) -> Self {
+		ReloadRequest {
+			mention: mention.into(),
+			in_reply_to_id: in_reply_to_id.into(),
+			visibility,
+			reloaded_message: reloaded_message.into(),
+			failed_message: failed_message.into(),
+			context: LogContext::current(),
+		}
+	}
+
+	fn reply(self, text: String) -> Message {
+		Message::Status {
+			text,
+			spoiler_text: None,
+			language: None,
+			visibility: self.visibility,
+			mention: Some(self.mention),
+			in_reply_to_id: Some(self.in_reply_to_id),
+			poll_options: None,
+			priority: Priority::Normal,
+			context: self.context,
+		}
+	}
+}
+
+/// Data loaded from the data files, which is replaced by reloading without restarting the workers.
+pub struct Reloader {
+	clock: Arc<dyn Clock>,
+	data: Arc<Swappable<DataSet>>,
+}
+
+impl Reloader {
+	pub fn load(clock: Arc<dyn Clock>) -> Result<Self> {
+		let data = DataSet::load(Arc::clone(&clock))?;
+
+		Ok(Reloader {
+			clock,
+			data: Arc::new(Swappable::new(data)),
+		})
+	}
+
+	pub fn data(&self) -> Arc<Swappable<DataSet>> {
+		Arc::clone(&self.data)
+	}
+
+	/// Validate and load all data files, and replace the current data only when all of them are loaded.
+	/// The current data is kept when any problem is found.
+	pub fn reload(&self) -> Result<()> {
+		info!("Start reloading data files");
+
+		let validator = validation::validate_data_files();
+		if !validator.problems().is_empty() {
+			for problem in validator.problems() {
+				error!("Problem found in data files: {}", problem);
+			}
+			return Err(Error::InvalidDataFiles(validator.problems().len()));
+		}
+
+		self.data.store(DataSet::load(Arc::clone(&self.clock))?);
+
+		info!("Reloading data files is complete");
+		Ok(())
+	}
+
+	/// Reload the data files, and build the reply to the admin who requested it if any.
+	pub fn reload_for(&self, request: Option<ReloadRequest>) -> Option<Message> {
+		let result = self.reload();
+		if let Err(e) = result.as_ref() {
+			error!("Failed to reload data files, keep the current data: {}", e);
+		}
+
+		request.map(|request| match result {
+			Ok(_) => {
+				let text = request.reloaded_message.clone();
+				request.reply(text)
+			},
+			Err(e) => {
+				let text = format!("{}\n{}", request.failed_message, e);
+				request.reply(text)
+			},
+		})
+	}
+}
+
+/// Data files which are loaded together, and replaced as a whole so that readers never see a mix of old and new data.
+pub struct DataSet {
+	contents: Contents,
+	status_processor: StatusProcessor,
+	notification_processor: NotificationProcessor,
+}
+
+impl DataSet {
+	fn load(clock: Arc<dyn Clock>) -> Result<Self> {
+		let monsters = Monsters::load()?;
+
+		Ok(DataSet {
+			contents: Contents::load(&monsters)?,
+			status_processor: StatusProcessor::load(clock, &monsters)?,
+			notification_processor: NotificationProcessor::load()?,
+		})
+	}
+
+	pub fn contents(&self) -> &Contents {
+		&self.contents
+	}
+
+	pub fn status_processor(&self) -> &StatusProcessor {
+		&self.status_processor
+	}
+
+	pub fn notification_processor(&self) -> &NotificationProcessor {
+		&self.notification_processor
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_swappable() {
+		let swappable = Swappable::new(1);
+		let before = swappable.load();
+
+		swappable.store(2);
+
+		assert_eq!(*before, 1);
+		assert_eq!(*swappable.load(), 2);
+	}
+
+	#[test]
+	fn test_wait_for_change() {
+		let swappable = Arc::new(Swappable::new(1));
+		let version = swappable.version();
+
+		assert!(!swappable.wait_for_change(version, Duration::from_millis(10)));
+
+		let writer = Arc::clone(&swappable);
+		let handle = std::thread::spawn(move || {
+			std::thread::sleep(Duration::from_millis(50));
+			writer.store(2);
+		});
+
+		assert!(swappable.wait_for_change(version, Duration::from_secs(10)));
+		assert_eq!(*swappable.load(), 2);
+		assert!(swappable.wait_for_change(version, Duration::from_millis(10)));
+		handle.join().unwrap();
+	}
+}
@@ -0,0 +1,47 @@
+use std::process;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::sync::mpsc;
+use std::thread;
+use signal_hook::consts::{ SIGHUP, SIGINT, SIGTERM };
+use signal_hook::iterator::Signals;
+use crate::{
+	Message,
+	Result,
+};
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Listen to the signals in a background thread, and pass them to the main loop as messages.
+/// SIGHUP reloads the data files, SIGTERM and SIGINT shut down the bot gracefully.
+/// The second SIGTERM or SIGINT exits immediately without waiting for the shutdown.
+pub fn listen(tx: mpsc::Sender<Message>) -> Result<()> {
+	let mut signals = Signals::new(&[SIGHUP, SIGINT, SIGTERM])?;
+
+	thread::Builder::new()
+		.name("signals".to_owned())
+		.spawn(move || {
+			for signal in signals.forever() {
+				info!("Signal received: {}", signal);
+
+				let message = match signal {
+					SIGHUP => Message::Reload(None),
+					_ if SHUTDOWN_REQUESTED.swap(true, Ordering::SeqCst) => {
+						warn!("Exit drakeema without waiting for the shutdown");
+						process::exit(1);
+					},
+					_ => Message::Shutdown,
+				};
+
+				if tx.send(message).is_err() {
+					break;
+				}
+			}
+		})?;
+
+	Ok(())
+}
+
+/// Whether the shutdown has been requested, workers stop taking new jobs once it returns `true`.
+pub fn shutdown_requested() -> bool {
+	SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,134 @@
+use regex::Regex;
+
+/// Length of a URL counted by Mastodon regardless of the actual length.
+const URL_LENGTH: usize = 23;
+const SEPARATORS: [&str; 2] = ["\n\n", "\n"];
+
+lazy_static! {
+	static ref URL_PLACEHOLDER: String = "x".repeat(URL_LENGTH);
+	static ref URL_REGEX: Regex = Regex::new(r#"https?://[^\s<>"]+"#).unwrap();
+	static ref MENTION_REGEX: Regex = Regex::new(r#"(^|[^/\w])@(\w+)@[\w.\-]+\w"#).unwrap();
+}
+
+/// Count the length of the text in the same way as Mastodon.
+///
+/// URLs are counted as 23 characters and the domain part of mentions are not counted.
+/// Custom emojis are counted by the length of its shortcode, so the text must be emojified before counting.
+pub fn count(text: impl AsRef<str>) -> usize {
+	let text = URL_REGEX.replace_all(text.as_ref(), URL_PLACEHOLDER.as_str());
+	let text = MENTION_REGEX.replace_all(&text, "$1@$2");
+
+	text.chars().count()
+}
+
+/// Split the text into parts that fit within the limit.
+///
+/// The text is split at paragraph boundaries first, then at line boundaries,
+/// and at character boundaries only when a line is still too long.
+pub fn split(text: impl AsRef<str>, limit: usize) -> Vec<String> {
+	let text = text.as_ref().trim();
+
+	if count(text) <= limit {
+		return vec![text.to_owned()];
+	}
+
+	split_by(text, limit, &SEPARATORS)
+		.into_iter()
+		.map(|part| part.trim().to_owned())
+		.filter(|part| !part.is_empty())
+		.collect()
+}
+
+fn split_by(text: &str, limit: usize, separators: &[&str]) -> Vec<String> {
+	if count(text) <= limit {
+		return vec![text.to_owned()];
+	}
+
+	let (separator, rest) = match separators.split_first() {
+		Some((separator, rest)) => (*separator, rest),
+		None => return split_chars(text, limit),
+	};
+
+	let mut parts: Vec<String> = Vec::new();
+	let mut current = String::new();
+
+	for unit in text.split(separator) {
+		for piece in split_by(unit, limit, rest) {
+			let joined = if current.is_empty() {
+				piece.clone()
+			} else {
+				current.clone() + separator + &piece
+			};
+
+			if count(&joined) <= limit {
+				current = joined;
+			} else {
+				parts.push(current);
+				current = piece;
+			}
+		}
+	}
+
+	if !current.is_empty() {
+		parts.push(current);
+	}
+
+	parts
+}
+
+fn split_chars(text: &str, limit: usize) -> Vec<String> {
+	text.chars()
+		.collect::<Vec<char>>()
+		.chunks(std::cmp::max(limit, 1))
+		.map(|chunk| chunk.iter().collect::<String>())
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_count() {
+		assert_eq!(count("キーマさん"), 5);
+		assert_eq!(count("see https://example.com/very/long/path/to/the/page?query=string"), 4 + URL_LENGTH);
+		assert_eq!(count("@kedama@foresdon.jp こんにちは"), 7 + 1 + 5);
+		assert_eq!(count("@kedama こんにちは"), 7 + 1 + 5);
+		assert_eq!(count(":m_drakeema: です"), 12 + 1 + 2);
+	}
+
+	#[test]
+	fn test_not_split() {
+		assert_eq!(split("あいうえお", 5), vec!["あいうえお"]);
+	}
+
+	#[test]
+	fn test_split_at_paragraphs() {
+		assert_eq!(
+			split("あいう\n\nかきく\n\nさしす", 8),
+			vec!["あいう\n\nかきく", "さしす"]
+		);
+	}
+
+	#[test]
+	fn test_split_at_lines() {
+		assert_eq!(
+			split("あいう\nかきく\nさしす\n\nたちつ", 7),
+			vec!["あいう\nかきく", "さしす", "たちつ"]
+		);
+	}
+
+	#[test]
+	fn test_split_at_chars() {
+		assert_eq!(
+			split("あいうえおかきくけこ", 4),
+			vec!["あいうえ", "おかきく", "けこ"]
+		);
+	}
+
+	#[test]
+	fn test_split_keeps_urls() {
+		let text = "https://example.com/a/very/long/url/which/is/counted/as/23/chars\n\nあいう";
+		assert_eq!(split(text, 30), vec![text]);
+	}
+}
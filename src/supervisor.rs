@@ -0,0 +1,136 @@
+use std::any::Any;
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{ Duration, Instant };
+use crate::{
+	Message,
+	backoff::Backoff,
+	signals,
+};
+
+const RESTART_BASE_SECS: u64 = 1;
+const RESTART_MAX_SECS: u64 = 300;
+/// A worker which has been running longer than this is regarded as recovered.
+const STABLE_SECS: u64 = 600;
+
+/// Long running job which sends messages to `MessageProcessor`.
+pub trait Worker: Send + Sync + 'static {
+	fn name(&self) -> &'static str;
+
+	/// Run the job until it finishes, the supervisor restarts the job when it returns or panics.
+	fn run(&self, tx: mpsc::Sender<Message>);
+}
+
+/// Watch workers and restart them with backoff when they panic or exit, until the shutdown is requested.
+pub struct Supervisor {
+	tx: mpsc::Sender<Message>,
+	backoff: Backoff,
+	stable: Duration,
+}
+
+impl Supervisor {
+	pub fn new(tx: mpsc::Sender<Message>) -> Self {
+		Supervisor {
+			tx,
+			backoff: Backoff::new(
+				Duration::from_secs(RESTART_BASE_SECS),
+				Duration::from_secs(RESTART_MAX_SECS),
+			),
+			stable: Duration::from_secs(STABLE_SECS),
+		}
+	}
+
+	pub fn spawn(&self, worker: impl Worker) -> thread::JoinHandle<()> {
+		let worker = Arc::new(worker);
+		let tx = mpsc::Sender::clone(&self.tx);
+		let mut backoff = self.backoff.clone();
+		let stable = self.stable;
+
+		info!("Start supervising worker: {}", worker.name());
+		thread::spawn(move || { loop {
+			let started_at = Instant::now();
+
+			let result = {
+				let worker = Arc::clone(&worker);
+				let tx = mpsc::Sender::clone(&tx);
+				thread::Builder::new()
+					.name(worker.name().to_owned())
+					.spawn(move || worker.run(tx))
+					.map(|handle| handle.join())
+			};
+
+			match result {
+				Ok(Ok(())) => warn!("Worker exited: {}", worker.name()),
+				Ok(Err(e)) => error!("Worker panicked: {}, {}", worker.name(), panic_message(&*e)),
+				Err(e) => error!("Failed to spawn worker: {}, {}", worker.name(), e),
+			};
+
+			if signals::shutdown_requested() {
+				info!("Stop supervising worker: {}", worker.name());
+				break;
+			}
+
+			if started_at.elapsed() >= stable {
+				backoff.reset();
+			}
+
+			let delay = backoff.next_delay();
+			warn!(
+				"Restart worker in {} secs: {}, restarts: {}",
+				delay.as_secs_f64(), worker.name(), backoff.attempt()
+			);
+			thread::sleep(delay);
+		}})
+	}
+}
+
+fn panic_message(e: &(dyn Any + Send)) -> &str {
+	if let Some(s) = e.downcast_ref::<&str>() {
+		s
+	} else if let Some(s) = e.downcast_ref::<String>() {
+		s
+	} else {
+		"unknown panic"
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::sync::atomic::{ AtomicUsize, Ordering };
+
+	struct PanickingWorker {
+		runs: Arc<AtomicUsize>,
+	}
+
+	impl Worker for PanickingWorker {
+		fn name(&self) -> &'static str {
+			"panicking"
+		}
+
+		fn run(&self, _tx: mpsc::Sender<Message>) {
+			self.runs.fetch_add(1, Ordering::SeqCst);
+			panic!("bad data");
+		}
+	}
+
+	#[test]
+	fn test_restart_after_panic() {
+		let (tx, _rx) = mpsc::channel();
+		let supervisor = Supervisor {
+			tx,
+			backoff: Backoff::new(Duration::from_millis(1), Duration::from_millis(10)),
+			stable: Duration::from_secs(STABLE_SECS),
+		};
+
+		let runs = Arc::new(AtomicUsize::new(0));
+		supervisor.spawn(PanickingWorker { runs: Arc::clone(&runs) });
+
+		let started_at = Instant::now();
+		while runs.load(Ordering::SeqCst) < 3 {
+			assert!(started_at.elapsed() < Duration::from_secs(5), "worker was not restarted");
+			thread::sleep(Duration::from_millis(1));
+		}
+	}
+}
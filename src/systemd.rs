@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::env;
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+#[cfg(target_os = "linux")]
+use std::os::unix::net::SocketAddr;
+use std::os::unix::net::UnixDatagram;
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicU64, Ordering };
+use std::thread;
+use std::time::{ Duration, Instant };
+use crate::Result;
+
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+const WATCHDOG_USEC: &str = "WATCHDOG_USEC";
+
+lazy_static! {
+	static ref LIVENESSES: Mutex<HashMap<u64, Entry>> = Mutex::new(HashMap::new());
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Tell systemd that the bot has started up.
+pub fn ready() {
+	notify("READY=1");
+}
+
+/// Tell systemd that the bot is shutting down.
+pub fn stopping() {
+	notify("STOPPING=1");
+}
+
+/// Show the text in `systemctl status`.
+pub fn status(text: impl AsRef<str>) {
+	notify(&format!("STATUS={}", text.as_ref().replace('\n', " ")));
+}
+
+/// Ping the watchdog in a background thread while all components named `expected` are alive.
+/// Nothing is done when the watchdog is not enabled in the unit.
+pub fn watch(expected: Vec<&'static str>) -> Result<()> {
+	let timeout = match watchdog_timeout(env::var(WATCHDOG_USEC).ok().as_deref()) {
+		Some(timeout) => timeout,
+		None => {
+			debug!("Watchdog is not enabled");
+			return Ok(());
+		},
+	};
+
+	info!("Start pinging watchdog: timeout: {} secs, components: {:?}", timeout.as_secs_f64(), expected);
+	thread::Builder::new()
+		.name("watchdog".to_owned())
+		.spawn(move || loop {
+			match unhealthy(&expected, Instant::now()) {
+				None => notify("WATCHDOG=1"),
+				Some(name) => warn!("Skip pinging watchdog because the component is not alive: {}", name),
+			};
+			thread::sleep(timeout / 2);
+		})?;
+
+	Ok(())
+}
+
+/// Mark of a component which is alive while this value is alive.
+/// The component also has to beat within the timeout if it is given.
+#[derive(Debug)]
+pub struct Liveness {
+	id: u64,
+}
+
+impl Liveness {
+	pub fn new(name: &'static str) -> Self {
+		Self::register(name, None)
+	}
+
+	pub fn with_timeout(name: &'static str, timeout: Duration) -> Self {
+		Self::register(name, Some(timeout))
+	}
+
+	fn register(name: &'static str, timeout: Option<Duration>) -> Self {
+		let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+		livenesses().insert(id, Entry {
+			name,
+			timeout,
+			last_beat: Instant::now(),
+		});
+
+		Liveness {
+			id,
+		}
+	}
+
+	pub fn beat(&self) {
+		if let Some(entry) = livenesses().get_mut(&self.id) {
+			entry.last_beat = Instant::now();
+		}
+	}
+}
+
+impl Drop for Liveness {
+	fn drop(&mut self) {
+		livenesses().remove(&self.id);
+	}
+}
+
+#[derive(Debug)]
+struct Entry {
+	name: &'static str,
+	timeout: Option<Duration>,
+	last_beat: Instant,
+}
+
+impl Entry {
+	fn is_alive(&self, now: Instant) -> bool {
+		match self.timeout {
+			Some(timeout) => now.saturating_duration_since(self.last_beat) <= timeout,
+			None => true,
+		}
+	}
+}
+
+fn livenesses() -> std::sync::MutexGuard<'static, HashMap<u64, Entry>> {
+	LIVENESSES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Find the expected component which is not alive.
+fn unhealthy(expected: &[&'static str], now: Instant) -> Option<&'static str> {
+	let livenesses = livenesses();
+
+	if let Some(entry) = livenesses.values().find(|entry| !entry.is_alive(now)) {
+		return Some(entry.name);
+	}
+
+	expected.iter()
+		.find(|name| !livenesses.values().any(|entry| entry.name == **name))
+		.copied()
+}
+
+fn watchdog_timeout(usec: Option<&str>) -> Option<Duration> {
+	usec.and_then(|usec| usec.parse::<u64>().ok())
+		.filter(|usec| *usec > 0)
+		.map(Duration::from_micros)
+}
+
+/// Send the state to systemd, errors are only logged because the bot works without systemd.
+fn notify(state: &str) {
+	let path = match env::var(NOTIFY_SOCKET) {
+		Ok(path) => path,
+		Err(_) => return,
+	};
+
+	if let Err(e) = send(&path, state) {
+		warn!("Failed to notify systemd: {}, {}", state, e);
+	}
+}
+
+fn send(path: &str, state: &str) -> std::io::Result<()> {
+	let socket = UnixDatagram::unbound()?;
+
+	match path.strip_prefix('@') {
+		Some(name) => send_to_abstract(&socket, name, state)?,
+		None => socket.send_to(state.as_bytes(), path)?,
+	};
+
+	Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn send_to_abstract(socket: &UnixDatagram, name: &str, state: &str) -> std::io::Result<usize> {
+	socket.send_to_addr(state.as_bytes(), &SocketAddr::from_abstract_name(name)?)
+}
+
+/// Abstract sockets are only on Linux, where systemd runs.
+#[cfg(not(target_os = "linux"))]
+fn send_to_abstract(_socket: &UnixDatagram, name: &str, _state: &str) -> std::io::Result<usize> {
+	Err(std::io::Error::new(
+		std::io::ErrorKind::Unsupported,
+		format!("abstract socket is not supported on this platform: @{}", name),
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_watchdog_timeout() {
+		assert_eq!(watchdog_timeout(Some("30000000")), Some(Duration::from_secs(30)));
+		assert_eq!(watchdog_timeout(Some("0")), None);
+		assert_eq!(watchdog_timeout(Some("abc")), None);
+		assert_eq!(watchdog_timeout(None), None);
+	}
+
+	#[test]
+	fn test_unhealthy() {
+		let now = Instant::now();
+		let expected = vec!["test main", "test listener"];
+
+		let main = Liveness::with_timeout("test main", Duration::from_secs(10));
+		assert_eq!(unhealthy(&expected, now), Some("test listener"));
+
+		let listener = Liveness::new("test listener");
+		assert_eq!(unhealthy(&expected, now), None);
+		assert_eq!(unhealthy(&expected, now + Duration::from_secs(11)), Some("test main"));
+
+		main.beat();
+		drop(listener);
+		assert_eq!(unhealthy(&expected, Instant::now()), Some("test listener"));
+	}
+}
@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::PathBuf;
+use chrono::{ DateTime, TimeZone };
+use chrono_tz::{ Asia::Tokyo, Tz };
+use mastors::entities::{ Notification, Status };
+use crate::{ clock::FakeClock, fake };
+
+const ACCT: &str = "kedama";
+const VISIBILITY: &str = "public";
+
+/// Create an empty directory for the test in the temporary directory of the system.
+pub fn temp_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("drakeema-test-{}-{}", std::process::id(), name));
+
+	if dir.exists() {
+		fs::remove_dir_all(&dir).unwrap();
+	}
+	fs::create_dir_all(&dir).unwrap();
+
+	dir
+}
+
+/// Clock fixed at the same time for every test.
+pub fn clock() -> FakeClock {
+	FakeClock::new(Tokyo.with_ymd_and_hms(2020, 9, 6, 6, 0, 0).unwrap())
+}
+
+/// Public status posted by another user.
+pub fn status(id: &str, text: &str, at: DateTime<Tz>) -> Status {
+	let mut json = fake::status(text, ACCT, VISIBILITY, at);
+	json["id"] = id.into();
+	serde_json::from_value(json).unwrap()
+}
+
+/// Notification of the public mention from another user.
+pub fn notification(id: &str, text: &str, at: DateTime<Tz>) -> Notification {
+	let mut json = fake::notification(text, ACCT, VISIBILITY, at);
+	json["id"] = id.into();
+	serde_json::from_value(json).unwrap()
+}
@@ -0,0 +1,47 @@
+use chrono::{ DateTime, Utc };
+use chrono_tz::Tz;
+use crate::{
+	Error,
+	Result,
+	config,
+};
+
+const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Tokyo;
+
+lazy_static! {
+	static ref TIMEZONE: Tz = load().unwrap();
+}
+
+/// Load the timezone of the game from the configuration, Asia/Tokyo is used when it is not set.
+pub fn load() -> Result<Tz> {
+	match config::get().timezone() {
+		Some(name) => parse(name),
+		None => Ok(DEFAULT_TIMEZONE),
+	}
+}
+
+/// Timezone in which all contents are calculated, announced and formatted,
+/// regardless of the timezone of the host.
+pub fn timezone() -> Tz {
+	*TIMEZONE
+}
+
+pub fn now() -> DateTime<Tz> {
+	Utc::now().with_timezone(&timezone())
+}
+
+fn parse(name: &str) -> Result<Tz> {
+	name.parse::<Tz>().map_err(|e| Error::InvalidTimezone(name.to_owned(), e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		assert_eq!(parse("Asia/Tokyo").unwrap(), chrono_tz::Asia::Tokyo);
+		assert_eq!(parse("UTC").unwrap(), chrono_tz::UTC);
+		assert!(parse("Asia/Edo").is_err());
+	}
+}
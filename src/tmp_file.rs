@@ -1,20 +1,19 @@
 use std::fs;
-use std::path::Path;
+use std::path::{ Path, PathBuf };
 
 use crate::{
 	Error,
 	Result,
+	config,
 };
 
-const TMP_PATH: &str = "drakeema-data/tmp/";
-
 pub fn save_tmp(file: impl AsRef<str>, data: impl AsRef<str>) -> Result<()> {
-	let path = TMP_PATH.to_string() + file.as_ref();
-	let path = Path::new(&path);
+	let path = config::state_path(file.as_ref());
+	let path = path.as_path();
 
 	debug!("Start saving data to a temporary file: path: {:?}, data: {}", path, data.as_ref());
 
-	fs::write(path, data.as_ref().trim())
+	replace_file(path, data.as_ref().trim())
 		.map_err(|e| Error::SaveTmpData(
 			path.to_string_lossy().to_string(), e
 		)
@@ -24,9 +23,34 @@ pub fn save_tmp(file: impl AsRef<str>, data: impl AsRef<str>) -> Result<()> {
 	Ok(())
 }
 
+/// Write to another file and replace with it, so that a crash while writing doesn't truncate the data.
+pub fn replace_file(path: &Path, data: impl AsRef<[u8]>) -> std::io::Result<()> {
+	let mut partial = path.as_os_str().to_owned();
+	partial.push(".partial");
+
+	fs::write(&partial, data)?;
+	fs::rename(&partial, path)
+}
+
+pub fn tmp_dir(dir: impl AsRef<str>) -> Result<PathBuf> {
+	let path = config::state_path(dir.as_ref());
+
+	if !path.exists() {
+		debug!("Create a temporary directory: {:?}", path);
+
+		fs::create_dir_all(&path)
+			.map_err(|e| Error::SaveTmpData(
+				path.to_string_lossy().to_string(), e
+			)
+		)?;
+	}
+
+	Ok(path)
+}
+
 pub fn load_tmp_as_string(file: impl AsRef<str>) -> Result<Option<String>> {
-	let path = TMP_PATH.to_string() + file.as_ref();
-	let path = Path::new(&path);
+	let path = config::state_path(file.as_ref());
+	let path = path.as_path();
 
 	debug!("Start loading data as string from a temporary file: {:?}", path);
 
@@ -48,8 +72,8 @@ pub fn load_tmp_as_string(file: impl AsRef<str>) -> Result<Option<String>> {
 }
 
 pub fn load_tmp_as_i64(file: impl AsRef<str>) -> Result<Option<i64>> {
-	let path = format!("{}/{}", TMP_PATH, file.as_ref());
-	info!("Start loading data as i64 from a temporary file: {}", path);
+	let path = config::state_path(file.as_ref());
+	info!("Start loading data as i64 from a temporary file: {:?}", path);
 
 	match load_tmp_as_string(file) {
 		Ok(t) => match t {
@@ -57,7 +81,7 @@ pub fn load_tmp_as_i64(file: impl AsRef<str>) -> Result<Option<i64>> {
 				debug!("Parssing data as i64: {}", s);
 				let data = s.parse::<i64>().map_err(|e| Error::TmpDataFormat(s.to_owned(), e))?;
 
-				debug!("Loading data as i64 from a temporary file is complete: {}, data: {}", path, data);
+				debug!("Loading data as i64 from a temporary file is complete: {:?}, data: {}", path, data);
 				Ok(Some(data))
 			},
 			None => Ok(None),
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::str::FromStr;
+use chrono::DateTime;
+use chrono_tz::Tz;
 use regex::Regex;
 use serde::{
 	de::{
@@ -19,6 +22,17 @@ where
 	Regex::from_str(&s).map_err(D::Error::custom)
 }
 
+/// Deserialize the date time in RFC 3339 format, and convert it to the timezone of the game.
+pub fn transform_string_to_datetime<'de, D>(deserializer: D) -> std::result::Result<DateTime<Tz>, D::Error>
+where
+	D: de::Deserializer<'de>,
+{
+	let s = String::deserialize(deserializer)?;
+	DateTime::parse_from_rfc3339(&s)
+		.map(|dt| dt.with_timezone(&crate::timezone::timezone()))
+		.map_err(D::Error::custom)
+}
+
 pub fn transform_vec_string_to_vec_regex<'de, D>(deserializer: D) -> std::result::Result<Vec<Regex>, D::Error>
 where
 	D: de::Deserializer<'de>,
@@ -47,3 +61,13 @@ where
 
 	deserializer.deserialize_seq(RegexVisitor)
 }
+
+pub fn transform_map_string_to_map_regex<'de, D>(deserializer: D) -> std::result::Result<HashMap<String, Regex>, D::Error>
+where
+	D: de::Deserializer<'de>,
+{
+	HashMap::<String, String>::deserialize(deserializer)?
+		.into_iter()
+		.map(|(name, s)| Regex::from_str(&s).map(|re| (name, re)).map_err(D::Error::custom))
+		.collect()
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use crate::{
+	config,
+	contents::{
+		Template,
+		boueigun,
+		jashin,
+		keema,
+		konmeiko,
+		panigulm,
+		seishugosha,
+		weekly_contents,
+	},
+	emojis::EmojiConfig,
+	features,
+	monsters::{ self, Monster },
+};
+
+lazy_static! {
+	static ref PLACEHOLDER_REGEX: Regex = Regex::new(r"__[A-Z0-9_]+?__").unwrap();
+}
+
+/// Problem found in a data file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+	file: String,
+	field: String,
+	message: String,
+}
+
+impl fmt::Display for Problem {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}: {}: {}", self.file, self.field, self.message)
+	}
+}
+
+/// Collect problems of data files without stopping at the first one.
+#[derive(Debug, Clone, Default)]
+pub struct Validator {
+	common_placeholders: Vec<String>,
+	problems: Vec<Problem>,
+}
+
+impl Validator {
+	pub fn new() -> Self {
+		Validator::default()
+	}
+
+	/// Allow the placeholder in every template, such as the placeholder of emojis.
+	pub fn allow_placeholder(&mut self, placeholder: impl Into<String>) {
+		self.common_placeholders.push(placeholder.into());
+	}
+
+	pub fn push(&mut self, file: impl Into<String>, field: impl Into<String>, message: impl Into<String>) {
+		self.problems.push(Problem {
+			file: file.into(),
+			field: field.into(),
+			message: message.into(),
+		});
+	}
+
+	/// Parse the data file in the data root, or record the problem and return `None` when it cannot be parsed.
+	pub fn parse<T: DeserializeOwned>(&mut self, file: &str) -> Option<T> {
+		debug!("Start validating {}", file);
+
+		let bytes = match fs::read(config::data_path(file)) {
+			Ok(bytes) => bytes,
+			Err(e) => {
+				self.push(file, "-", format!("cannot read the file: {}", e));
+				return None;
+			},
+		};
+
+		match serde_json::from_slice(&bytes) {
+			Ok(data) => Some(data),
+			Err(e) => {
+				self.push(file, "-", format!("cannot parse the file: {}", e));
+				None
+			},
+		}
+	}
+
+	/// Check the template has no placeholders other than the given ones.
+	pub fn check_template(&mut self, file: &str, field: &str, template: &Template, placeholders: &[&str]) {
+		self.check_placeholders(file, field, template, placeholders);
+		if let Some(spoiler_text) = template.spoiler_text() {
+			self.check_placeholders(file, &format!("{}.spoiler_text", field), spoiler_text, placeholders);
+		}
+	}
+
+	/// Check the text has no placeholders other than the given ones.
+	pub fn check_placeholders(&mut self, file: &str, field: &str, text: &str, placeholders: &[&str]) {
+		let unknowns = PLACEHOLDER_REGEX.find_iter(text)
+			.map(|m| m.as_str())
+			.filter(|p| !placeholders.contains(p))
+			.filter(|p| !self.common_placeholders.iter().any(|c| c == p))
+			.map(|p| p.to_owned())
+			.collect::<Vec<String>>();
+
+		for unknown in unknowns {
+			self.push(file, field, format!("unknown placeholder: {}", unknown));
+		}
+	}
+
+	/// Check the monster ID is known, and return the monster.
+	pub fn check_monster_id<'a>(
+		&mut self,
+		file: &str,
+		field: &str,
+		monsters: &'a HashMap<String, Monster>,
+		monster_id: &str,
+	) -> Option<&'a Monster> {
+		let monster = monsters.get(monster_id);
+		if monster.is_none() {
+			self.push(file, field, format!("unknown monster ID: {}", monster_id));
+		}
+		monster
+	}
+
+	/// Check the monster has resistances for a single area,
+	/// which is required where the resistances are displayed without area names.
+	pub fn check_single_area(&mut self, file: &str, field: &str, monster: &Monster) {
+		if monster.resistances().len() > 1 {
+			self.push(file, field, format!(
+				"resistances for multiple areas are given but no area_names is available: {}",
+				monster.id()
+			));
+		}
+	}
+
+	pub fn check_not_empty<T>(&mut self, file: &str, field: &str, items: &[T]) {
+		if items.is_empty() {
+			self.push(file, field, "at least one element is required");
+		}
+	}
+
+	pub fn problems(&self) -> &[Problem] {
+		&self.problems
+	}
+}
+
+/// Validate all data files, and return the validator which has the problems found in them.
+pub fn validate_data_files() -> Validator {
+	let mut validator = Validator::new();
+
+	if let Some(config) = EmojiConfig::validate(&mut validator) {
+		validator.allow_placeholder(config.placeholder());
+	}
+
+	let monsters = monsters::validate(&mut validator);
+	jashin::validate(&mut validator, &monsters);
+	konmeiko::validate(&mut validator, &monsters);
+	panigulm::validate(&mut validator, &monsters);
+	boueigun::validate(&mut validator, &monsters);
+	seishugosha::validate(&mut validator, &monsters);
+	weekly_contents::validate(&mut validator);
+	keema::validate(&mut validator);
+	features::announcement::validate(&mut validator);
+	features::response::validate(&mut validator);
+
+	validator
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_placeholders() {
+		let mut validator = Validator::new();
+		validator.allow_placeholder("__EMOJI__");
+
+		validator.check_placeholders("a.json", "announcement", "__TITLE__ です __EMOJI__", &["__TITLE__"]);
+		assert!(validator.problems().is_empty());
+
+		validator.check_placeholders("a.json", "announcement", "__TITEL__ と __MONSTER1__", &["__TITLE__"]);
+		assert_eq!(
+			validator.problems().iter().map(|p| p.to_string()).collect::<Vec<String>>(),
+			vec![
+				"a.json: announcement: unknown placeholder: __TITEL__",
+				"a.json: announcement: unknown placeholder: __MONSTER1__",
+			]
+		);
+	}
+
+	#[test]
+	fn test_check_template() {
+		let template: Template = serde_json::from_str(r#"{
+			"text": "__TITLE__",
+			"spoiler_text": "__MONSTERS__"
+		}"#).unwrap();
+
+		let mut validator = Validator::new();
+		validator.check_template("a.json", "information", &template, &["__TITLE__"]);
+		assert_eq!(
+			validator.problems().iter().map(|p| p.to_string()).collect::<Vec<String>>(),
+			vec!["a.json: information.spoiler_text: unknown placeholder: __MONSTERS__"]
+		);
+	}
+}